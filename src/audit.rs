@@ -1,9 +1,27 @@
 #![forbid(unsafe_code)]
 
+use crate::host::HostPattern;
+use crate::json::Json;
+use crate::manifest::{
+    Capabilities, Connect, FileRead, Files, Manifest, Memory, Network, Sockets, Syscalls,
+    UnixSockets, is_manifest_path,
+};
 use anyhow::{Context, Result, anyhow};
 use goblin::elf;
 use regex::Regex;
-use std::{collections::BTreeSet, fs, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    sync::LazyLock,
+};
+
+mod ldso;
+mod ltrace;
+mod macho;
+mod pe;
+mod perf;
+mod strace;
 
 fn map_machine(m: u16) -> &'static str {
     use goblin::elf::header::*;
@@ -18,53 +36,203 @@ fn map_machine(m: u16) -> &'static str {
     }
 }
 
-pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
-    let buf =
-        fs::read(&path).with_context(|| format!("failed to read {}", path.as_ref().display()))?;
+/// Binary hardening features relevant to sandboxing decisions.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Hardening {
+    pub pie: bool,
+    pub nx: bool,
+    pub relro: bool,
+    pub bind_now: bool,
+    pub stack_canary: bool,
+    /// `*_chk` symbols pulled in by `_FORTIFY_SOURCE`.
+    pub fortified: BTreeSet<String>,
+    pub rpath: Vec<String>,
+    pub runpath: Vec<String>,
+}
 
-    // --- Basic ELF parse (goblin) ---
-    let elf = elf::Elf::parse(&buf).map_err(|e| anyhow!("not a valid ELF: {e}"))?;
-    let is_pie = elf.header.e_type == goblin::elf::header::ET_DYN;
+impl Hardening {
+    pub fn from_elf(elf: &elf::Elf) -> Hardening {
+        let is_pie = elf.header.e_type == goblin::elf::header::ET_DYN;
 
-    let has_gnu_relro = elf
-        .program_headers
-        .iter()
-        .any(|ph| ph.p_type == goblin::elf::program_header::PT_GNU_RELRO);
-    let bind_now = elf
-        .dynamic
-        .as_ref()
-        .map(|dyninfo| {
-            use goblin::elf::dynamic::*;
-            let mut now = false;
-            for d in &dyninfo.dyns {
-                match d.d_tag {
-                    DT_BIND_NOW => now = true,
-                    DT_FLAGS => {
-                        let v = d.d_val;
-                        if (v & (DF_BIND_NOW as u64)) != 0 {
-                            now = true;
+        let has_gnu_relro = elf
+            .program_headers
+            .iter()
+            .any(|ph| ph.p_type == goblin::elf::program_header::PT_GNU_RELRO);
+        let bind_now = elf
+            .dynamic
+            .as_ref()
+            .map(|dyninfo| {
+                use goblin::elf::dynamic::*;
+                let mut now = false;
+                for d in &dyninfo.dyns {
+                    match d.d_tag {
+                        DT_BIND_NOW => now = true,
+                        DT_FLAGS => {
+                            let v = d.d_val;
+                            if (v & DF_BIND_NOW) != 0 {
+                                now = true;
+                            }
                         }
-                    }
-                    DT_FLAGS_1 => {
-                        let v = d.d_val;
-                        if (v & (DF_1_NOW as u64)) != 0 {
-                            now = true;
+                        DT_FLAGS_1 => {
+                            let v = d.d_val;
+                            if (v & DF_1_NOW) != 0 {
+                                now = true;
+                            }
                         }
+                        _ => {}
                     }
-                    _ => {}
                 }
-            }
-            now
-        })
-        .unwrap_or(false);
+                now
+            })
+            .unwrap_or(false);
+
+        // NX: check PT_GNU_STACK executable flag
+        let nx_enabled = elf
+            .program_headers
+            .iter()
+            .find(|ph| ph.p_type == goblin::elf::program_header::PT_GNU_STACK)
+            .map(|ph| ph.p_flags & goblin::elf::program_header::PF_X == 0)
+            .unwrap_or(true);
+
+        let (stack_canary, fortified) = hardening_symbols(elf);
+        Hardening {
+            pie: is_pie,
+            nx: nx_enabled,
+            relro: has_gnu_relro,
+            bind_now,
+            stack_canary,
+            fortified,
+            rpath: elf.rpaths.iter().map(|s| s.to_string()).collect(),
+            runpath: elf.runpaths.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// RPATH/RUNPATH entries that let files outside the install location be loaded.
+    pub fn insecure_search_paths(&self) -> Vec<String> {
+        self.rpath
+            .iter()
+            .chain(&self.runpath)
+            .flat_map(|s| s.split(':'))
+            .filter(|e| is_insecure_search_path(e))
+            .map(|e| e.to_string())
+            .collect()
+    }
 
-    // NX: check PT_GNU_STACK executable flag
-    let nx_enabled = elf
-        .program_headers
+    /// 0..=100; each feature carries a fixed weight.
+    pub fn score(&self) -> u64 {
+        let mut score = 0;
+        if self.pie {
+            score += 20;
+        }
+        if self.nx {
+            score += 20;
+        }
+        if self.relro {
+            score += if self.bind_now { 20 } else { 10 };
+        }
+        if self.stack_canary {
+            score += 20;
+        }
+        if !self.fortified.is_empty() {
+            score += 10;
+        }
+        if self.insecure_search_paths().is_empty() {
+            score += 10;
+        }
+        score
+    }
+}
+
+/// Relative and empty entries resolve against the CWD; /tmp-like dirs are world-writable.
+fn is_insecure_search_path(entry: &str) -> bool {
+    if entry.is_empty() {
+        return true;
+    }
+    if entry.starts_with("$ORIGIN") || entry.starts_with("${ORIGIN}") {
+        return false;
+    }
+    !entry.starts_with('/')
+        || ["/tmp", "/var/tmp", "/dev/shm"]
+            .iter()
+            .any(|d| entry == *d || entry.starts_with(&format!("{d}/")))
+}
+
+/// Scan dynamic and static symbol tables for canary and fortify markers.
+fn hardening_symbols(elf: &elf::Elf) -> (bool, BTreeSet<String>) {
+    let mut canary = false;
+    let mut fortified = BTreeSet::new();
+    let dynamic = elf
+        .dynsyms
         .iter()
-        .find(|ph| ph.p_type == goblin::elf::program_header::PT_GNU_STACK)
-        .map(|ph| ph.p_flags & goblin::elf::program_header::PF_X == 0)
-        .unwrap_or(true);
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name));
+    let local = elf
+        .syms
+        .iter()
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name));
+    for name in dynamic.chain(local) {
+        if name.starts_with("__stack_chk_fail") {
+            canary = true;
+        } else if name.starts_with("__") && name.ends_with("_chk") {
+            fortified.insert(name.to_string());
+        }
+    }
+    (canary, fortified)
+}
+
+/// Thin and fat Mach-O magics, in either byte order.
+fn is_macho(buf: &[u8]) -> bool {
+    use goblin::mach::{fat::FAT_MAGIC, header::*};
+    buf.get(..4)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_le_bytes)
+        .is_some_and(|m| {
+            [
+                MH_MAGIC,
+                MH_CIGAM,
+                MH_MAGIC_64,
+                MH_CIGAM_64,
+                FAT_MAGIC,
+                FAT_MAGIC.swap_bytes(),
+            ]
+            .contains(&m)
+        })
+}
+
+/// Parse the ELF at `path` and return only its hardening summary.
+pub fn elf_hardening<P: AsRef<Path>>(path: P) -> Result<Hardening> {
+    let buf =
+        fs::read(&path).with_context(|| format!("failed to read {}", path.as_ref().display()))?;
+    let elf = elf::Elf::parse(&buf).map_err(|e| anyhow!("not a valid ELF: {e}"))?;
+    Ok(Hardening::from_elf(&elf))
+}
+
+pub fn audit_elf<P: AsRef<Path>>(
+    path: P,
+    json: Option<&Path>,
+    manifest_out: Option<&Path>,
+) -> Result<()> {
+    let buf =
+        fs::read(&path).with_context(|| format!("failed to read {}", path.as_ref().display()))?;
+
+    // PE and Mach-O get their own, smaller audits
+    if buf.starts_with(b"MZ") {
+        let pe = goblin::pe::PE::parse(&buf).map_err(|e| anyhow!("not a valid PE: {e}"))?;
+        return pe::audit(path.as_ref(), &buf, &pe, json, manifest_out);
+    }
+    if is_macho(&buf) {
+        return macho::audit(path.as_ref(), &buf, json, manifest_out);
+    }
+
+    // --- Basic ELF parse (goblin) ---
+    let elf = elf::Elf::parse(&buf).map_err(|e| anyhow!("not a valid ELF: {e}"))?;
+    let hardening = Hardening::from_elf(&elf);
+    let (is_pie, nx_enabled, has_gnu_relro, bind_now) = (
+        hardening.pie,
+        hardening.nx,
+        hardening.relro,
+        hardening.bind_now,
+    );
 
     // Imported symbols we care about (network/files/process/etc.)
     let mut imports = BTreeSet::new();
@@ -73,10 +241,10 @@ pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
             if sym.st_name == 0 {
                 continue;
             }
-            if let Some(name) = elf.dynstrtab.get_at(sym.st_name) {
-                if is_interesting_symbol(name) {
-                    imports.insert(name.to_string());
-                }
+            if let Some(name) = elf.dynstrtab.get_at(sym.st_name)
+                && is_interesting_symbol(name)
+            {
+                imports.insert(name.to_string());
             }
         }
     }
@@ -84,18 +252,30 @@ pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
     // Needed shared libraries
     let needed: BTreeSet<_> = elf.libraries.iter().map(|s| s.to_string()).collect();
 
+    // Linking: PT_INTERP means the kernel hands off to a dynamic loader
+    let interp = elf.interpreter.map(|s| s.to_string());
+    let linking = linking_kind(interp.is_some(), elf.dynamic.is_some());
+    let libc = detect_libc(interp.as_deref(), &needed);
+    let closure = ldso::resolve_closure(&elf, path.as_ref());
+
+    let insecure = hardening.insecure_search_paths();
+
     // --------------- strings: use section-bounded scan -----------------
     let ascii_strings = strings_from_elf_sections(&elf, &buf, 4); // Strings: harvest candidate hosts and config paths
 
-    let path_re = Regex::new(r#"(/(?:etc|var|usr|home)/[^\s"']+)"#).unwrap();
-
-    let mut paths = BTreeSet::new();
-    for s in &ascii_strings {
-        if let Some(c) = path_re.captures(s) {
-            paths.insert(c[1].to_string());
-        }
+    let paths = paths_from_strings(&ascii_strings);
+    // Static Go/Rust binaries never import libc socket calls; look at their runtimes too
+    let language = detect_language(&elf);
+    let mut net_evidence = Vec::new();
+    if has_net_intent_from_imports(&imports) {
+        net_evidence.push("libc socket/DNS imports".to_string());
     }
-    let net_intent = has_net_intent_from_imports(&imports);
+    net_evidence.extend(runtime_net_markers(&elf, &ascii_strings));
+    let url_hosts = hosts_from_urls(&ascii_strings);
+    if !url_hosts.is_empty() {
+        net_evidence.push("embedded http(s) URLs".to_string());
+    }
+    let net_intent = !net_evidence.is_empty();
 
     // Report
     println!("== ELF Audit ==");
@@ -113,6 +293,30 @@ pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
 
     let full_relro = has_gnu_relro && bind_now;
     println!("Full RELRO          : {}", yesno(full_relro));
+    println!("Linking: {}", linking);
+    if let Some(i) = &interp {
+        println!("Interpreter (PT_INTERP): {}", i);
+    }
+    println!("libc: {}", libc);
+    println!("Stack canary        : {}", yesno(hardening.stack_canary));
+    println!(
+        "FORTIFY_SOURCE      : {}",
+        if hardening.fortified.is_empty() {
+            "no".to_string()
+        } else {
+            format!("yes ({} *_chk symbols)", hardening.fortified.len())
+        }
+    );
+    for r in &hardening.rpath {
+        println!("RPATH  : {}", r);
+    }
+    for r in &hardening.runpath {
+        println!("RUNPATH: {}", r);
+    }
+    for r in &insecure {
+        eprintln!("⚠️  Insecure library search path: {:?}", r);
+    }
+    println!("Hardening score: {}/100", hardening.score());
 
     if !needed.is_empty() {
         println!("\nShared libs (DT_NEEDED):");
@@ -121,6 +325,19 @@ pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
         }
     }
 
+    if !closure.resolved.is_empty() {
+        println!("\nShared lib closure (resolved on this host):");
+        for (name, p) in &closure.resolved {
+            println!("  - {} => {}", name, p.display());
+        }
+    }
+    if !closure.missing.is_empty() {
+        println!("\nShared libs not found:");
+        for n in &closure.missing {
+            println!("  - {}", n);
+        }
+    }
+
     if !imports.is_empty() {
         println!("\nInteresting imports:");
         for i in &imports {
@@ -135,71 +352,243 @@ pub fn audit_elf<P: AsRef<Path>>(path: P) -> Result<()> {
         }
     }
 
-    println!("\nNetwork capability required: {}", yesno(net_intent));
+    println!("\nLanguage/runtime: {}", language);
+    println!("Network capability required: {}", yesno(net_intent));
+    for e in &net_evidence {
+        println!("  - {}", e);
+    }
+
+    // The loader and every library it maps must be readable inside the sandbox
+    let mut read_paths = paths.clone();
+    if let Some(i) = &interp {
+        read_paths.insert(i.clone());
+    }
+    read_paths.extend(closure.read_paths());
+    if !needed.is_empty() {
+        eprintln!(
+            "\n⚠️  Dynamically linked: the DT_NEEDED libraries above (and the loader) must be readable inside the sandbox."
+        );
+    }
 
     // Suggested manifest skeleton
+    let name = path
+        .as_ref()
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("app");
+    let hosts = net_intent.then_some(url_hosts.clone());
+    let mut suggested = suggested_manifest(name, &read_paths, hosts.as_ref());
+    // without dynamic imports there is nothing to estimate from
+    let syscalls = (!imports.is_empty()).then(|| syscalls_from_imports(&imports));
+    if let Some(allow) = &syscalls {
+        suggested.capabilities.syscalls = Some(Syscalls {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+        });
+    }
     println!("\n== Suggested manifest (skeleton) ==");
-    println!(
-        "name = \"{}\"",
-        path.as_ref()
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("app")
-    );
-    println!("version = \"0.0.0\"");
-    println!();
-    println!("[capabilities.memory]");
-    println!("max_bytes = 134217728  # TODO: adjust");
-    if !paths.is_empty() {
-        println!("\n[capabilities.files.read]");
-        print!("paths = [");
-        print_csv(&paths);
-        println!("]");
+    println!("# TODO: adjust capabilities.memory.max_bytes");
+    if syscalls.is_some() {
+        println!("# capabilities.syscalls: static estimate from imports - verify with trace audit");
+    }
+    print!("{}", suggested);
+    if let Some(m) = manifest_out {
+        write_manifest(m, &suggested)?;
     }
-    if net_intent {
-        println!("\n[capabilities.network.connect]");
-        print!("hosts = []\n");
+
+    if let Some(j) = json {
+        let report = Json::obj()
+            .with("file", path.as_ref().display().to_string())
+            .with("machine", map_machine(elf.header.e_machine))
+            .with("linking", linking)
+            .with("interpreter", interp.clone())
+            .with("libc", libc)
+            .with(
+                "hardening",
+                Json::obj()
+                    .with("pie", hardening.pie)
+                    .with("nx", hardening.nx)
+                    .with("relro", hardening.relro)
+                    .with("bind_now", hardening.bind_now)
+                    .with("full_relro", full_relro)
+                    .with("stack_canary", hardening.stack_canary)
+                    .with(
+                        "fortified",
+                        hardening.fortified.iter().cloned().collect::<Vec<_>>(),
+                    )
+                    .with("rpath", hardening.rpath.clone())
+                    .with("runpath", hardening.runpath.clone())
+                    .with("insecure_search_paths", insecure.clone())
+                    .with("score", hardening.score()),
+            )
+            .with("needed", needed.iter().cloned().collect::<Vec<_>>())
+            .with(
+                "library_closure",
+                closure
+                    .resolved
+                    .values()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .with(
+                "missing_libraries",
+                closure.missing.iter().cloned().collect::<Vec<_>>(),
+            )
+            .with("imports", imports.iter().cloned().collect::<Vec<_>>())
+            .with(
+                "syscalls_estimate",
+                syscalls.map(|s| s.into_iter().map(str::to_string).collect::<Vec<_>>()),
+            )
+            .with("paths", paths.iter().cloned().collect::<Vec<_>>())
+            .with("language", language)
+            .with("network", net_intent)
+            .with("network_evidence", net_evidence.clone())
+            .with("url_hosts", url_hosts.iter().cloned().collect::<Vec<_>>());
+        fs::write(j, format!("{report}\n"))
+            .with_context(|| format!("failed to write {}", j.display()))?;
     }
 
     Ok(())
 }
 
-pub fn audit_trace<P: AsRef<Path>>(path: P) -> Result<()> {
-    let s = fs::read_to_string(&path)
-        .with_context(|| format!("failed to read {}", path.as_ref().display()))?;
+/// One syscall from a trace, independent of the tracer's text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallEvent {
+    /// Thread/process id when the tracer recorded one (`strace -f`).
+    pub pid: Option<u32>,
+    pub name: String,
+    /// Raw argument text as printed by the tracer.
+    pub args: String,
+    /// Numeric return value, if the call finished and returned a number.
+    pub ret: Option<i64>,
+}
+
+impl SyscallEvent {
+    fn failed(&self) -> bool {
+        self.ret.is_some_and(|r| r < 0)
+    }
+}
+
+/// Files and endpoints touched by one process (or the whole trace).
+#[derive(Debug, Default)]
+struct Activity {
+    reads: BTreeSet<String>,
+    writes: BTreeSet<String>,
+    hosts: BTreeSet<String>,
+    sockets: BTreeSet<String>,
+}
+
+const OPEN_SYSCALLS: &[&str] = &["open", "openat", "openat2", "creat"];
+const NET_SYSCALLS: &[&str] = &["connect", "sendto", "sendmsg", "getaddrinfo"];
+
+/// Text format of a trace log given to `audit trace`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceFormat {
+    #[default]
+    Strace,
+    Ltrace,
+    Perf,
+}
+
+impl std::str::FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "strace" => Ok(TraceFormat::Strace),
+            "ltrace" => Ok(TraceFormat::Ltrace),
+            "perf" => Ok(TraceFormat::Perf),
+            other => Err(format!(
+                "unknown trace format {other:?} (expected strace, ltrace or perf)"
+            )),
+        }
+    }
+}
 
-    // very light extraction from strace text logs
+impl TraceFormat {
+    pub fn parse(self, log: &str) -> Vec<SyscallEvent> {
+        match self {
+            TraceFormat::Strace => strace::parse(log),
+            TraceFormat::Ltrace => ltrace::parse(log),
+            TraceFormat::Perf => perf::parse(log),
+        }
+    }
+}
+
+/// Activity over the whole trace and per pid; `None` is the traced process itself,
+/// whose lines `strace -f` leaves without a `[pid N]` prefix.
+fn collect_activity(events: &[SyscallEvent]) -> (Activity, BTreeMap<Option<u32>, Activity>) {
     let host_re =
         Regex::new(r#"([a-zA-Z0-9][a-zA-Z0-9\.-]*\.[a-zA-Z]{2,})(?::(\d{2,5}))?"#).unwrap();
-    let path_re = Regex::new(r#""(/[^"\s]+)""#).unwrap();
-
-    let mut hosts = BTreeSet::new();
-    let mut reads = BTreeSet::new();
-    let mut writes = BTreeSet::new();
-
-    for line in s.lines() {
-        for c in host_re.captures_iter(line) {
-            let host = match (c.get(1), c.get(2)) {
-                (Some(h), Some(p)) => format!("{}:{}", h.as_str(), p.as_str()),
-                (Some(h), None) => h.as_str().to_string(),
-                _ => continue,
-            };
-            hosts.insert(host);
-        }
-
-        if line.contains("open") || line.contains("openat") {
-            for c in path_re.captures_iter(line) {
-                let p = c[1].to_string();
-                // naive: decide RO/RW based on flags in the line
-                if line.contains("O_WRONLY") || line.contains("O_RDWR") || line.contains("O_CREAT")
-                {
-                    writes.insert(p);
+    let mut total = Activity::default();
+    let mut per_pid: BTreeMap<Option<u32>, Activity> = BTreeMap::new();
+
+    for ev in events {
+        let mut act = Activity::default();
+
+        // failed opens are probes (e.g. library search); a failed non-blocking
+        // connect (EINPROGRESS) still shows network intent
+        if OPEN_SYSCALLS.contains(&ev.name.as_str()) && !ev.failed() {
+            if let Some(p) = first_path_arg(&ev.args) {
+                // decide RO/RW based on open flags
+                let writes = ev.name == "creat"
+                    || ev.args.contains("O_WRONLY")
+                    || ev.args.contains("O_RDWR")
+                    || ev.args.contains("O_CREAT");
+                if writes {
+                    act.writes.insert(p);
                 } else {
-                    reads.insert(p);
+                    act.reads.insert(p);
+                }
+            }
+        } else if NET_SYSCALLS.contains(&ev.name.as_str()) {
+            // checked first: socket file names like docker.sock would look like hosts
+            if let Some(p) = unix_socket_path(&ev.args) {
+                act.sockets.insert(p);
+            } else {
+                act.hosts.extend(sockaddr_endpoint(&ev.args));
+                for c in host_re.captures_iter(&ev.args) {
+                    let host = match (c.get(1), c.get(2)) {
+                        (Some(h), Some(p)) => format!("{}:{}", h.as_str(), p.as_str()),
+                        (Some(h), None) => h.as_str().to_string(),
+                        _ => continue,
+                    };
+                    act.hosts.insert(host);
                 }
+                act.hosts.retain(|h| is_manifest_host(h));
             }
         }
+
+        // unprefixed lines come from the traced process itself
+        let slot = per_pid.entry(ev.pid).or_default();
+        slot.reads.extend(act.reads.iter().cloned());
+        slot.writes.extend(act.writes.iter().cloned());
+        slot.hosts.extend(act.hosts.iter().cloned());
+        slot.sockets.extend(act.sockets.iter().cloned());
+        total.reads.extend(act.reads);
+        total.writes.extend(act.writes);
+        total.hosts.extend(act.hosts);
+        total.sockets.extend(act.sockets);
     }
+    (total, per_pid)
+}
+
+pub fn audit_trace<P: AsRef<Path>>(
+    path: P,
+    format: TraceFormat,
+    manifest_out: Option<&Path>,
+) -> Result<()> {
+    let s = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.as_ref().display()))?;
+    let events = format.parse(&s);
+
+    let (total, per_pid) = collect_activity(&events);
+    let Activity {
+        reads,
+        writes,
+        hosts,
+        sockets,
+    } = total;
 
     println!("== Trace Audit ==");
     println!("File: {}", path.as_ref().display());
@@ -222,25 +611,64 @@ pub fn audit_trace<P: AsRef<Path>>(path: P) -> Result<()> {
             println!("  - {}", h);
         }
     }
+    if !sockets.is_empty() {
+        println!("\nUnix sockets:");
+        for p in &sockets {
+            println!("  - {}", p);
+        }
+    }
+
+    if per_pid.len() > 1 {
+        println!("\nPer-process activity:");
+        for (pid, act) in &per_pid {
+            match pid {
+                Some(pid) => println!("  [pid {}]", pid),
+                None => println!("  [main]"),
+            }
+            for p in &act.reads {
+                println!("    read  {}", p);
+            }
+            for p in &act.writes {
+                println!("    write {}", p);
+            }
+            for h in &act.hosts {
+                println!("    net   {}", h);
+            }
+            for p in &act.sockets {
+                println!("    unix  {}", p);
+            }
+        }
+    }
+
+    let fds = track_fds(&events);
+    println!("\nFile descriptors:");
+    println!("  Peak open      : {}", fds.peak);
+    println!("  Suggested nofile: {}", fds.suggested_nofile());
+    if !fds.leaked.is_empty() {
+        println!("  Open at exit (possible leaks):");
+        for (pid, fd) in &fds.leaked {
+            match pid {
+                Some(pid) => println!("    - {} (pid {})", fd, pid),
+                None => println!("    - {}", fd),
+            }
+        }
+    }
 
     // Suggested manifest from trace
-    println!("\n== Suggested manifest (from trace) ==");
-    println!("name = \"app\"");
-    println!("version = \"0.0.0\"");
-    println!();
-    println!("[capabilities.memory]");
-    println!("max_bytes = 134217728  # TODO: infer from mmap/brk");
-    if !reads.is_empty() {
-        println!("\n[capabilities.files.read]");
-        print!("paths = [");
-        print_csv(&reads);
-        println!("]");
+    let hosts = (!hosts.is_empty()).then_some(hosts);
+    let mut suggested = suggested_manifest("app", &reads, hosts.as_ref());
+    if !sockets.is_empty() {
+        suggested.capabilities.sockets = Some(Sockets {
+            unix: Some(UnixSockets {
+                paths: sockets.into_iter().collect(),
+            }),
+        });
     }
-    if !hosts.is_empty() {
-        println!("\n[capabilities.network.connect]");
-        print!("hosts = [");
-        print_csv(&hosts);
-        println!("]");
+    println!("\n== Suggested manifest (from trace) ==");
+    println!("# TODO: infer capabilities.memory.max_bytes from mmap/brk");
+    print!("{}", suggested);
+    if let Some(m) = manifest_out {
+        write_manifest(m, &suggested)?;
     }
     if !writes.is_empty() {
         eprintln!(
@@ -251,6 +679,153 @@ pub fn audit_trace<P: AsRef<Path>>(path: P) -> Result<()> {
     Ok(())
 }
 
+/// File descriptor usage observed in a trace.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FdUsage {
+    /// Highest number of fds open at once in any one process, stdio included.
+    pub peak: usize,
+    /// Non-stdio fds still open when the trace ends, by pid (`None` for the traced
+    /// process itself when the tracer left its lines unprefixed).
+    pub leaked: BTreeSet<(Option<u32>, i64)>,
+}
+
+impl FdUsage {
+    /// A `nofile` limit with 2x headroom over the observed peak, never below 64.
+    pub fn suggested_nofile(&self) -> u64 {
+        ((self.peak as u64) * 2).next_power_of_two().max(64)
+    }
+}
+
+/// Replay fd-creating and fd-closing syscalls from a trace, per process. Each
+/// process is assumed to start with stdio open.
+pub fn track_fds(events: &[SyscallEvent]) -> FdUsage {
+    // syscalls whose return value is a new fd
+    const FD_RET: &[&str] = &[
+        "open",
+        "openat",
+        "openat2",
+        "creat",
+        "socket",
+        "accept",
+        "accept4",
+        "dup",
+        "dup2",
+        "dup3",
+        "epoll_create",
+        "epoll_create1",
+        "eventfd",
+        "eventfd2",
+        "signalfd",
+        "signalfd4",
+        "timerfd_create",
+        "memfd_create",
+        "inotify_init",
+        "inotify_init1",
+        "pidfd_open",
+    ];
+    // syscalls that hand back an fd pair through their arguments
+    const FD_PAIR: &[&str] = &["pipe", "pipe2", "socketpair"];
+
+    let pair_re = Regex::new(r"\[(\d+),\s*(\d+)\]").unwrap();
+
+    let mut open: BTreeMap<Option<u32>, BTreeSet<i64>> = BTreeMap::new();
+    let mut peak = 3;
+
+    for ev in events {
+        let Some(ret) = ev.ret.filter(|r| *r >= 0) else {
+            continue;
+        };
+        let name = ev.name.as_str();
+        let args = ev.args.as_str();
+        let fds = open
+            .entry(ev.pid)
+            .or_insert_with(|| [0, 1, 2].into_iter().collect());
+
+        if name == "close" {
+            if let Ok(fd) = args.trim().parse::<i64>() {
+                fds.remove(&fd);
+            }
+        } else if FD_RET.contains(&name) {
+            // the kernel never hands out an fd that is still open; such returns are
+            // placeholders, e.g. ltrace's fopen normalized to 0
+            if !fds.insert(ret) {
+                continue;
+            }
+        } else if FD_PAIR.contains(&name)
+            && let Some(p) = pair_re.captures(args)
+        {
+            fds.extend(
+                [&p[1], &p[2]]
+                    .into_iter()
+                    .filter_map(|fd| fd.parse::<i64>().ok()),
+            );
+        }
+        peak = peak.max(fds.len());
+    }
+
+    FdUsage {
+        peak,
+        leaked: open
+            .into_iter()
+            .flat_map(|(pid, fds)| fds.into_iter().map(move |fd| (pid, fd)))
+            .filter(|(_, fd)| *fd > 2)
+            .collect(),
+    }
+}
+
+fn linking_kind(has_interp: bool, has_dynamic: bool) -> &'static str {
+    match (has_interp, has_dynamic) {
+        (true, _) => "dynamic",
+        (false, true) => "static-pie",
+        (false, false) => "static",
+    }
+}
+
+fn detect_libc(interp: Option<&str>, needed: &BTreeSet<String>) -> &'static str {
+    let musl =
+        interp.is_some_and(|i| i.contains("ld-musl")) || needed.iter().any(|n| n.contains("musl"));
+    let glibc = interp.is_some_and(|i| i.contains("ld-linux") || i.contains("ld64.so"))
+        || needed.iter().any(|n| n.starts_with("libc.so."));
+    match (musl, glibc) {
+        (true, _) => "musl",
+        (false, true) => "glibc",
+        _ => "unknown",
+    }
+}
+
+// compiled once: these run for every traced syscall
+static PORT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"sin6?_port=htons\((\d+)\)").unwrap());
+static V4_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"sin_addr=inet_addr\("([^"]+)"\)"#).unwrap());
+static V6_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"inet_pton\(AF_INET6,\s*"([^"]+)""#).unwrap());
+
+/// `ip:port` from an strace-rendered AF_INET/AF_INET6 sockaddr.
+fn sockaddr_endpoint(args: &str) -> Option<String> {
+    let port = PORT_RE.captures(args)?[1].to_string();
+    if let Some(c) = V4_RE.captures(args) {
+        return Some(format!("{}:{}", &c[1], port));
+    }
+    V6_RE
+        .captures(args)
+        .map(|c| format!("[{}]:{}", &c[1], port))
+}
+
+static SUN_PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"sun_path=(@?)"((?:[^"\\]|\\.)*)""#).unwrap());
+
+/// `sun_path` of an AF_UNIX sockaddr; abstract sockets come back as `@name`.
+fn unix_socket_path(args: &str) -> Option<String> {
+    if !args.contains("AF_UNIX") {
+        return None;
+    }
+    let c = SUN_PATH_RE.captures(args)?;
+    let path = String::from_utf8(unescape_c(&c[2])).ok()?;
+    Some(format!("{}{path}", &c[1]))
+}
+
 fn is_interesting_symbol(name: &str) -> bool {
     const KEYWORDS: &[&str] = &[
         "open",
@@ -282,6 +857,69 @@ fn is_interesting_symbol(name: &str) -> bool {
     KEYWORDS.iter().any(|k| name.contains(k))
 }
 
+/// Syscalls any dynamically linked program makes before and around `main`.
+const BASELINE_SYSCALLS: &[&str] = &[
+    "arch_prctl",
+    "brk",
+    "close",
+    "exit",
+    "exit_group",
+    "fstat",
+    "getrandom",
+    "lseek",
+    "mmap",
+    "mprotect",
+    "munmap",
+    "newfstatat",
+    "openat",
+    "prlimit64",
+    "read",
+    "rseq",
+    "rt_sigaction",
+    "rt_sigprocmask",
+    "rt_sigreturn",
+    "set_robust_list",
+    "set_tid_address",
+    "write",
+];
+
+/// Conservative syscall allowlist implied by libc imports. An over-approximation
+/// for the listed families; calls made through other symbols are not seen.
+fn syscalls_from_imports(imports: &BTreeSet<String>) -> BTreeSet<&'static str> {
+    let mut out: BTreeSet<&'static str> = BASELINE_SYSCALLS.iter().copied().collect();
+    for name in imports {
+        // "__read_chk", "fopen64", "recv@GLIBC_2.2.5" -> "read", "fopen", "recv"
+        let base = name.split('@').next().unwrap_or_default();
+        let base = base.trim_start_matches('_').trim_end_matches("_chk");
+        let base = base.strip_suffix("64").unwrap_or(base);
+        let family: &[&str] = match base {
+            "open" | "openat" | "fopen" | "freopen" => &["open", "openat"],
+            "read" | "pread" | "readv" => &["read", "pread64", "readv"],
+            "write" | "pwrite" | "writev" => &["write", "pwrite64", "writev"],
+            "socket" => &["socket", "setsockopt", "getsockopt"],
+            "connect" => &["connect", "getsockname", "getpeername"],
+            b if b.starts_with("send") => &["sendto", "sendmsg", "sendmmsg"],
+            b if b.starts_with("recv") => &["recvfrom", "recvmsg", "recvmmsg"],
+            "getaddrinfo" => &[
+                "socket", "connect", "sendto", "sendmmsg", "recvfrom", "poll", "ioctl",
+            ],
+            "fork" | "vfork" | "clone" => &["clone", "clone3", "vfork", "wait4"],
+            b if b.starts_with("exec") => &["execve", "execveat"],
+            "system" | "popen" => &["clone", "clone3", "execve", "wait4", "pipe2"],
+            "ptrace" => &["ptrace"],
+            "ioctl" => &["ioctl"],
+            "dlopen" => &["openat", "mmap", "mprotect", "fstat"],
+            "setuid" => &["setuid"],
+            "capset" => &["capset", "capget"],
+            "futex" => &["futex"],
+            "prctl" => &["prctl"],
+            _ => &[],
+        };
+        out.extend(family);
+    }
+    out
+}
+
 /// Extract ASCII-ish strings from a byte slice
 fn extract_ascii_strings(buf: &[u8], min: usize) -> Vec<String> {
     let mut out = Vec::new();
@@ -290,18 +928,18 @@ fn extract_ascii_strings(buf: &[u8], min: usize) -> Vec<String> {
         if (0x20..=0x7E).contains(&b) || b == b'\t' {
             cur.push(b);
         } else if !cur.is_empty() {
-            if cur.len() >= min {
-                if let Ok(s) = String::from_utf8(cur.clone()) {
-                    out.push(s);
-                }
+            if cur.len() >= min
+                && let Ok(s) = String::from_utf8(cur.clone())
+            {
+                out.push(s);
             }
             cur.clear();
         }
     }
-    if cur.len() >= min {
-        if let Ok(s) = String::from_utf8(cur) {
-            out.push(s);
-        }
+    if cur.len() >= min
+        && let Ok(s) = String::from_utf8(cur)
+    {
+        out.push(s);
     }
     out
 }
@@ -334,6 +972,122 @@ fn strings_from_elf_sections<'a>(elf: &elf::Elf<'a>, bytes: &'a [u8], min: usize
     }
 }
 
+fn symbol_names<'a>(elf: &'a elf::Elf) -> impl Iterator<Item = &'a str> {
+    let dynamic = elf
+        .dynsyms
+        .iter()
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name));
+    let local = elf
+        .syms
+        .iter()
+        .filter_map(|sym| elf.strtab.get_at(sym.st_name));
+    dynamic.chain(local)
+}
+
+fn detect_language(elf: &elf::Elf) -> &'static str {
+    let has_section = |name: &str| {
+        elf.section_headers
+            .iter()
+            .any(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(name))
+    };
+    if has_section(".go.buildinfo") || has_section(".gopclntab") {
+        return "go";
+    }
+    if symbol_names(elf).any(|n| n.contains("__rust_") || n.contains("rust_begin_unwind")) {
+        return "rust";
+    }
+    "unknown"
+}
+
+/// Networking entry points of the Go and Rust runtimes. Go keeps function names in
+/// .gopclntab even when stripped, so these are matched against strings as well.
+fn runtime_net_markers(elf: &elf::Elf, strings: &[String]) -> Vec<String> {
+    const GO: &[&str] = &[
+        "net.(*Dialer)",
+        "net.Dial",
+        "net.(*Resolver)",
+        "net.Listen",
+        "net/http.(*Client)",
+        "net/http.(*Transport)",
+        "crypto/tls.(*Conn)",
+    ];
+    // legacy mangling (`_ZN3std3net...`), demangled names and std source paths
+    const RUST: &[&str] = &[
+        "3std3net",
+        "std::net::",
+        "std/src/net",
+        "5tokio3net",
+        "tokio::net::",
+        "3mio3net",
+        "7reqwest",
+        "5hyper",
+        "7rustls",
+    ];
+
+    let mut out = Vec::new();
+    let mut check = |lang: &str, markers: &[&str]| {
+        let hit = markers.iter().find(|m| {
+            symbol_names(elf).any(|n| n.contains(*m)) || strings.iter().any(|s| s.contains(*m))
+        });
+        if let Some(m) = hit {
+            out.push(format!("{lang} runtime networking ({m})"));
+        }
+    };
+    check("Go", GO);
+    check("Rust", RUST);
+    out
+}
+
+/// Longest path `paths_from_strings` will suggest; anything longer is packed string data.
+const MAX_STRING_PATH: usize = 256;
+
+/// Config-looking paths in binary strings. A path ends at whitespace, a quote, `:`
+/// or `,`. A string that is nothing but one path may have spaces in its directory
+/// names (`/etc/My App/config.toml`). Format strings (`%s`) are skipped.
+fn paths_from_strings(strings: &[String]) -> BTreeSet<String> {
+    let path_re = Regex::new(r#"/(?:etc|var|usr|home)/[^\s"',:]+"#).unwrap();
+    let whole_re =
+        Regex::new(r#"^/(?:etc|var|usr|home)/(?:[^\s"',:/]+(?: [^\s"',:/]+)*/)*[^\s"',:/]+$"#)
+            .unwrap();
+    strings
+        .iter()
+        .filter_map(|s| match whole_re.find(s) {
+            Some(m) => Some(m.as_str()),
+            None => path_re.find(s).map(|m| m.as_str()),
+        })
+        .map(|p| p.trim_end_matches(['.', ';', ')']))
+        .filter(|p| p.len() <= MAX_STRING_PATH && !p.contains('%'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Suggested hosts must pass the same checks `connect.hosts` does in a manifest.
+fn is_manifest_host(h: &str) -> bool {
+    h.parse::<HostPattern>().is_ok()
+}
+
+/// `host:port` for every http(s) URL found in the binary's strings.
+fn hosts_from_urls(strings: &[String]) -> BTreeSet<String> {
+    let url_re = Regex::new(
+        r"\b(https?)://([A-Za-z0-9](?:[A-Za-z0-9.-]*[A-Za-z0-9])?\.[A-Za-z]{2,}|\d{1,3}(?:\.\d{1,3}){3})(?::(\d{1,5}))?",
+    )
+    .unwrap();
+    let mut out = BTreeSet::new();
+    for s in strings {
+        for c in url_re.captures_iter(s) {
+            let port =
+                c.get(3)
+                    .map(|p| p.as_str())
+                    .unwrap_or(if &c[1] == "https" { "443" } else { "80" });
+            let host = format!("{}:{}", &c[2], port);
+            if is_manifest_host(&host) {
+                out.insert(host);
+            }
+        }
+    }
+    out
+}
+
 fn has_net_intent_from_imports(imports: &std::collections::BTreeSet<String>) -> bool {
     // cover common libc + OpenSSL entry points; `contains` handles versioned names (e.g. "connect@@GLIBC_2.2.5")
     const NET_SYMS: &[&str] = &[
@@ -377,17 +1131,375 @@ fn has_net_intent_from_imports(imports: &std::collections::BTreeSet<String>) ->
         .any(|s| NET_SYMS.iter().any(|p| s.contains(p)))
 }
 
-fn print_csv(set: &BTreeSet<String>) {
-    let mut first = true;
-    for v in set {
-        if !first {
-            print!(", ");
+/// Build the suggested manifest as a value so the TOML serializer does the quoting;
+/// paths may contain spaces, quotes, newlines or non-ASCII text.
+fn suggested_manifest(
+    name: &str,
+    reads: &BTreeSet<String>,
+    hosts: Option<&BTreeSet<String>>,
+) -> Manifest {
+    Manifest {
+        name: name.to_string(),
+        version: "0.0.0".to_string(),
+        preset: None,
+        capabilities: Capabilities {
+            memory: Some(Memory {
+                max_bytes: 134_217_728,
+            }),
+            files: (!reads.is_empty()).then(|| Files {
+                // paths a manifest would reject stay in the report only
+                read: Some(FileRead {
+                    paths: reads
+                        .iter()
+                        .filter(|p| is_manifest_path(p))
+                        .cloned()
+                        .collect(),
+                }),
+                deny_read: Vec::new(),
+                tmp: None,
+            }),
+            sockets: None,
+            syscalls: None,
+            devices: None,
+            ipc: None,
+            network: hosts.map(|h| Network {
+                connect: Some(Connect {
+                    hosts: h.iter().filter(|h| is_manifest_host(h)).cloned().collect(),
+                }),
+                listen: None,
+            }),
+        },
+        service: None,
+        runtime: None,
+        secrets: None,
+        profile: BTreeMap::new(),
+    }
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) -> Result<()> {
+    fs::write(path, manifest.to_string())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+static QUOTED_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""((?:[^"\\]|\\.)*)""#).unwrap());
+
+/// First quoted absolute path in tracer argument text, with C escapes decoded.
+/// Non-UTF-8 paths cannot be expressed in a TOML manifest and are skipped.
+fn first_path_arg(args: &str) -> Option<String> {
+    QUOTED_RE
+        .captures_iter(args)
+        .filter_map(|c| String::from_utf8(unescape_c(&c[1])).ok())
+        .find(|p| p.starts_with('/'))
+}
+
+/// Decode the C-style escapes tracers use for string arguments (`\n`, `\"`, `\303`, `\xc3`).
+fn unescape_c(s: &str) -> Vec<u8> {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] != b'\\' || i + 1 == b.len() {
+            out.push(b[i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+        match b[i] {
+            b'n' => out.push(b'\n'),
+            b't' => out.push(b'\t'),
+            b'r' => out.push(b'\r'),
+            b'v' => out.push(0x0b),
+            b'f' => out.push(0x0c),
+            b'x' => {
+                let hex: String = s[i + 1..]
+                    .chars()
+                    .take(2)
+                    .take_while(char::is_ascii_hexdigit)
+                    .collect();
+                out.push(u8::from_str_radix(&hex, 16).unwrap_or(b'x'));
+                i += hex.len();
+            }
+            b'0'..=b'7' => {
+                let oct: String = s[i..]
+                    .chars()
+                    .take(3)
+                    .take_while(|c| ('0'..='7').contains(c))
+                    .collect();
+                out.push(u8::from_str_radix(&oct, 8).unwrap_or(0));
+                i += oct.len() - 1;
+            }
+            other => out.push(other),
         }
-        first = false;
-        print!("{:?}", v); // quoted TOML string
+        i += 1;
     }
+    out
 }
 
 fn yesno(b: bool) -> &'static str {
     if b { "yes" } else { "no" }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_fds_counts_peak_and_leaks() {
+        let log = r#"openat(AT_FDCWD, "/etc/hosts", O_RDONLY|O_CLOEXEC) = 3
+socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 4
+pipe2([5, 6], O_CLOEXEC) = 0
+close(3) = 0
+openat(AT_FDCWD, "/missing", O_RDONLY) = -1 ENOENT (No such file or directory)
+close(5) = 0
+close(6) = 0
+exit_group(0) = ?
+"#;
+        let fds = track_fds(&strace::parse(log));
+        assert_eq!(fds.peak, 7);
+        assert_eq!(fds.leaked.into_iter().collect::<Vec<_>>(), [(None, 4)]);
+    }
+
+    #[test]
+    fn unprefixed_strace_lines_belong_to_main() {
+        let log = r#"openat(AT_FDCWD, "/etc/main.conf", O_RDONLY) = 3
+[pid 42] openat(AT_FDCWD, "/etc/child.conf", O_RDONLY) = 3
+"#;
+        let (total, per_pid) = collect_activity(&strace::parse(log));
+        assert_eq!(total.reads.len(), 2);
+        assert_eq!(per_pid.len(), 2);
+        assert!(per_pid[&None].reads.contains("/etc/main.conf"));
+        assert!(per_pid[&Some(42)].reads.contains("/etc/child.conf"));
+    }
+
+    #[test]
+    fn track_fds_keeps_processes_apart() {
+        let log = r#"[pid 10] openat(AT_FDCWD, "/a", O_RDONLY) = 3
+[pid 11] openat(AT_FDCWD, "/b", O_RDONLY) = 3
+[pid 10] close(3) = 0
+"#;
+        let fds = track_fds(&strace::parse(log));
+        assert_eq!(fds.peak, 4);
+        assert_eq!(fds.leaked.into_iter().collect::<Vec<_>>(), [(Some(11), 3)]);
+
+        // fopen's FILE* carries no fd; it must not count as a new fd 0
+        let fds = track_fds(&ltrace::parse("fopen(\"/etc/a\", \"r\") = 0x5581a2b0\n"));
+        assert_eq!(fds.peak, 3);
+        assert!(fds.leaked.is_empty());
+    }
+
+    #[test]
+    fn linking_and_libc_detection() {
+        assert_eq!(linking_kind(true, true), "dynamic");
+        assert_eq!(linking_kind(false, true), "static-pie");
+        assert_eq!(linking_kind(false, false), "static");
+
+        let glibc: BTreeSet<String> = ["libc.so.6".to_string()].into();
+        assert_eq!(
+            detect_libc(Some("/lib64/ld-linux-x86-64.so.2"), &glibc),
+            "glibc"
+        );
+        assert_eq!(
+            detect_libc(Some("/lib/ld-musl-x86_64.so.1"), &BTreeSet::new()),
+            "musl"
+        );
+        assert_eq!(detect_libc(None, &BTreeSet::new()), "unknown");
+    }
+
+    #[test]
+    fn hardening_score_and_insecure_search_paths() {
+        let mut h = Hardening {
+            pie: true,
+            nx: true,
+            relro: true,
+            bind_now: true,
+            stack_canary: true,
+            fortified: ["__memcpy_chk".to_string()].into(),
+            rpath: Vec::new(),
+            runpath: vec!["$ORIGIN/../lib:/opt/app/lib".to_string()],
+        };
+        assert!(h.insecure_search_paths().is_empty());
+        assert_eq!(h.score(), 100);
+
+        h.runpath = vec!["lib::/tmp/x".to_string()];
+        assert_eq!(h.insecure_search_paths(), ["lib", "", "/tmp/x"]);
+        h.bind_now = false;
+        assert_eq!(h.score(), 80);
+    }
+
+    #[test]
+    fn sockaddr_endpoint_formats_v4_and_v6() {
+        assert_eq!(
+            sockaddr_endpoint(
+                r#"3, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("93.184.216.34")}, 16"#
+            )
+            .as_deref(),
+            Some("93.184.216.34:443")
+        );
+        assert_eq!(
+            sockaddr_endpoint(
+                r#"3, {sa_family=AF_INET6, sin6_port=htons(53), sin6_flowinfo=htonl(0), inet_pton(AF_INET6, "::1", &sin6_addr), sin6_scope_id=0}, 28"#
+            )
+            .as_deref(),
+            Some("[::1]:53")
+        );
+        assert_eq!(
+            sockaddr_endpoint("3, {sa_family=AF_UNIX, sun_path=\"/run/x\"}, 110"),
+            None
+        );
+    }
+
+    #[test]
+    fn syscall_estimate_maps_import_families() {
+        let imports: BTreeSet<String> = ["__read_chk", "fopen64", "connect", "execvp"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let sys = syscalls_from_imports(&imports);
+        for expected in ["read", "openat", "connect", "execve", "exit_group"] {
+            assert!(sys.contains(expected), "missing {expected}");
+        }
+        assert!(!sys.contains("ptrace"));
+        assert!(!sys.contains("socket"));
+    }
+
+    #[test]
+    fn unix_socket_paths_are_not_mistaken_for_hosts() {
+        assert_eq!(
+            unix_socket_path(r#"3, {sa_family=AF_UNIX, sun_path="/var/run/docker.sock"}, 110"#)
+                .as_deref(),
+            Some("/var/run/docker.sock")
+        );
+        assert_eq!(
+            unix_socket_path(r#"3, {sa_family=AF_UNIX, sun_path=@"/tmp/.X11-unix/X0"}, 20"#)
+                .as_deref(),
+            Some("@/tmp/.X11-unix/X0")
+        );
+        assert_eq!(
+            unix_socket_path(
+                r#"3, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("1.2.3.4")}, 16"#
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn adversarial_paths_survive_trace_to_manifest() {
+        let log = r#"openat(AT_FDCWD, "/srv/my app/conf.toml", O_RDONLY) = 3
+openat(AT_FDCWD, "/srv/caf\303\251/\"q\"\\x", O_RDONLY) = 4
+openat(AT_FDCWD, "/srv/trailing\n", O_RDONLY) = 5
+openat(AT_FDCWD, "/srv/bad\377", O_RDONLY) = 6
+"#;
+        let reads: BTreeSet<String> = strace::parse(log)
+            .iter()
+            .filter_map(|e| first_path_arg(&e.args))
+            .collect();
+        let expected: BTreeSet<String> = [
+            "/srv/my app/conf.toml",
+            "/srv/café/\"q\"\\x",
+            "/srv/trailing\n",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(reads, expected);
+
+        let m = suggested_manifest("my app ü", &reads, None);
+        let reparsed = crate::manifest::parse_manifest(m.to_string().as_bytes())
+            .expect("suggested manifest must parse");
+        assert_eq!(reparsed.name, "my app ü");
+        for p in &expected {
+            let keep = !p.contains('\n');
+            assert_eq!(reparsed.may_read(p), keep, "{p:?}");
+        }
+    }
+
+    #[test]
+    fn hosts_from_urls_defaults_ports_by_scheme() {
+        let strings = vec![
+            "GET https://api.example.com/v1/items".to_string(),
+            "fallback=http://10.0.0.1:8080/health".to_string(),
+            "not a url: example.com".to_string(),
+        ];
+        let hosts: Vec<_> = hosts_from_urls(&strings).into_iter().collect();
+        assert_eq!(hosts, ["10.0.0.1:8080", "api.example.com:443"]);
+    }
+
+    #[test]
+    fn string_paths_keep_embedded_spaces() {
+        let strings = vec![
+            "/etc/My App/config.toml".to_string(),
+            "open '/var/lib/app/db' failed".to_string(),
+            "/usr/share/app/data  ".to_string(),
+        ];
+        let paths: Vec<_> = paths_from_strings(&strings).into_iter().collect();
+        assert_eq!(
+            paths,
+            [
+                "/etc/My App/config.toml",
+                "/usr/share/app/data",
+                "/var/lib/app/db"
+            ]
+        );
+    }
+
+    #[test]
+    fn string_paths_stop_at_surrounding_text() {
+        let strings = vec![
+            "/etc/hosts file.".to_string(),
+            "/usr/local/bin:/usr/local/sbin:/usr/bin:/bin:.".to_string(),
+            "/usr/local/sbin, /usr/sbin and /sbin".to_string(),
+            "/usr/share/apport/gcc_ice_hook %s %s".to_string(),
+            "/etc/app/%s.conf".to_string(),
+            "/usr/bin/sort          ->".to_string(),
+            format!("/usr/share/{}", "x".repeat(MAX_STRING_PATH)),
+        ];
+        let paths: Vec<_> = paths_from_strings(&strings).into_iter().collect();
+        assert_eq!(
+            paths,
+            [
+                "/etc/hosts",
+                "/usr/bin/sort",
+                "/usr/local/bin",
+                "/usr/local/sbin",
+                "/usr/share/apport/gcc_ice_hook",
+            ]
+        );
+    }
+
+    #[test]
+    fn suggested_hosts_pass_manifest_validation() {
+        let strings = vec![
+            "http://example.com:99999/".to_string(),
+            "https://example.com:0/".to_string(),
+            "https://ok.example.com:8443/".to_string(),
+        ];
+        let hosts: Vec<_> = hosts_from_urls(&strings).into_iter().collect();
+        assert_eq!(hosts, ["ok.example.com:8443"]);
+
+        let ev = |args: &str| SyscallEvent {
+            pid: None,
+            name: "connect".to_string(),
+            args: args.to_string(),
+            ret: Some(0),
+        };
+        let events = [ev("\"a..com:443\""), ev("\"db.internal.io:99999\"")];
+        let (total, _) = collect_activity(&events);
+        for h in &total.hosts {
+            assert!(is_manifest_host(h), "{h}");
+        }
+    }
+
+    #[test]
+    fn suggested_nofile_has_headroom_and_floor() {
+        let small = FdUsage {
+            peak: 5,
+            leaked: BTreeSet::new(),
+        };
+        assert_eq!(small.suggested_nofile(), 64);
+        let big = FdUsage {
+            peak: 300,
+            leaked: BTreeSet::new(),
+        };
+        assert_eq!(big.suggested_nofile(), 1024);
+    }
+}
@@ -0,0 +1,195 @@
+//! Minimal model of the dynamic loader's library search, used to suggest read paths.
+//!
+//! Order per object: DT_RPATH (only without DT_RUNPATH), DT_RUNPATH, /etc/ld.so.cache,
+//! then the default directories. `LD_LIBRARY_PATH` is ignored on purpose: it
+//! describes the auditing host, not the sandbox.
+
+use goblin::elf::Elf;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_DIRS: &[&str] = &[
+    "/lib64",
+    "/usr/lib64",
+    "/lib",
+    "/usr/lib",
+    "/lib/x86_64-linux-gnu",
+    "/usr/lib/x86_64-linux-gnu",
+    "/lib/aarch64-linux-gnu",
+    "/usr/lib/aarch64-linux-gnu",
+];
+
+const CACHE_MAGIC: &[u8] = b"glibc-ld.so.cache1.1";
+const CACHE_HEADER_LEN: usize = 48;
+const CACHE_ENTRY_LEN: usize = 24;
+
+#[derive(Debug, Default)]
+pub struct LibClosure {
+    /// soname -> path it resolved to on this host
+    pub resolved: BTreeMap<String, PathBuf>,
+    /// sonames that could not be found
+    pub missing: BTreeSet<String>,
+}
+
+impl LibClosure {
+    /// Paths the sandbox must be able to read: each resolved path plus its symlink target.
+    pub fn read_paths(&self) -> BTreeSet<String> {
+        let mut out = BTreeSet::new();
+        for p in self.resolved.values() {
+            out.insert(p.display().to_string());
+            if let Ok(real) = fs::canonicalize(p) {
+                out.insert(real.display().to_string());
+            }
+        }
+        out
+    }
+}
+
+/// Walk DT_NEEDED breadth-first from `elf`, which was loaded from `path`.
+pub fn resolve_closure(elf: &Elf, path: &Path) -> LibClosure {
+    let cache = load_cache(Path::new("/etc/ld.so.cache"));
+    let machine = elf.header.e_machine;
+    let is_64 = elf.is_64;
+
+    let mut out = LibClosure::default();
+    let mut queue: VecDeque<(String, Vec<PathBuf>)> = VecDeque::new();
+    let dirs = object_dirs(elf, path);
+    for lib in &elf.libraries {
+        queue.push_back((lib.to_string(), dirs.clone()));
+    }
+
+    while let Some((name, dirs)) = queue.pop_front() {
+        if out.resolved.contains_key(&name) || out.missing.contains(&name) {
+            continue;
+        }
+
+        let candidates = candidates(&name, &dirs, &cache);
+        let hit = candidates.into_iter().find_map(|p| {
+            let bytes = fs::read(&p).ok()?;
+            let lib = Elf::parse(&bytes).ok()?;
+            if lib.header.e_machine != machine || lib.is_64 != is_64 {
+                return None;
+            }
+            let dirs = object_dirs(&lib, &p);
+            let needed: Vec<String> = lib.libraries.iter().map(|s| s.to_string()).collect();
+            Some((p, dirs, needed))
+        });
+
+        match hit {
+            Some((p, dirs, needed)) => {
+                out.resolved.insert(name, p);
+                for n in needed {
+                    queue.push_back((n, dirs.clone()));
+                }
+            }
+            None => {
+                out.missing.insert(name);
+            }
+        }
+    }
+
+    out
+}
+
+fn candidates(
+    name: &str,
+    dirs: &[PathBuf],
+    cache: &BTreeMap<String, Vec<PathBuf>>,
+) -> Vec<PathBuf> {
+    // a slash in DT_NEEDED means a literal path
+    if name.contains('/') {
+        return vec![PathBuf::from(name)];
+    }
+    let mut out: Vec<PathBuf> = dirs.iter().map(|d| d.join(name)).collect();
+    if let Some(hits) = cache.get(name) {
+        out.extend(hits.iter().cloned());
+    }
+    out.extend(DEFAULT_DIRS.iter().map(|d| Path::new(d).join(name)));
+    out.into_iter().filter(|p| p.is_file()).collect()
+}
+
+/// RPATH/RUNPATH directories of one object with `$ORIGIN` expanded.
+fn object_dirs(elf: &Elf, path: &Path) -> Vec<PathBuf> {
+    let origin = path
+        .parent()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| ".".to_string());
+    let raw = if elf.runpaths.is_empty() {
+        &elf.rpaths
+    } else {
+        &elf.runpaths
+    };
+    raw.iter()
+        .flat_map(|s| s.split(':'))
+        .filter(|s| !s.is_empty())
+        .map(|s| PathBuf::from(s.replace("${ORIGIN}", &origin).replace("$ORIGIN", &origin)))
+        .collect()
+}
+
+/// Parse the "new format" glibc cache into soname -> paths. Unknown formats yield an empty map.
+fn load_cache(path: &Path) -> BTreeMap<String, Vec<PathBuf>> {
+    fs::read(path).map(|b| parse_cache(&b)).unwrap_or_default()
+}
+
+fn parse_cache(buf: &[u8]) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut out: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    if !buf.starts_with(CACHE_MAGIC) || buf.len() < CACHE_HEADER_LEN {
+        return out;
+    }
+    let u32_at = |off: usize| -> Option<usize> {
+        let b = buf.get(off..off + 4)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+    let cstr_at = |off: usize| -> Option<&str> {
+        let rest = buf.get(off..)?;
+        let end = rest.iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&rest[..end]).ok()
+    };
+
+    let Some(nlibs) = u32_at(20) else {
+        return out;
+    };
+    for i in 0..nlibs {
+        let entry = CACHE_HEADER_LEN + i * CACHE_ENTRY_LEN;
+        let (Some(key), Some(value)) = (u32_at(entry + 4), u32_at(entry + 8)) else {
+            break;
+        };
+        if let (Some(k), Some(v)) = (cstr_at(key), cstr_at(value)) {
+            out.entry(k.to_string()).or_default().push(PathBuf::from(v));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cache_reads_entries_and_rejects_garbage() {
+        assert!(parse_cache(b"not a cache").is_empty());
+
+        // header + one entry + string table
+        let mut buf = CACHE_MAGIC.to_vec();
+        buf.extend_from_slice(&1u32.to_le_bytes()); // nlibs
+        buf.resize(CACHE_HEADER_LEN, 0);
+        let strings = CACHE_HEADER_LEN + CACHE_ENTRY_LEN;
+        let key = b"libz.so.1\0";
+        let value = b"/usr/lib/libz.so.1\0";
+        buf.extend_from_slice(&0x0303i32.to_le_bytes());
+        buf.extend_from_slice(&(strings as u32).to_le_bytes());
+        buf.extend_from_slice(&((strings + key.len()) as u32).to_le_bytes());
+        buf.resize(strings, 0);
+        buf.extend_from_slice(key);
+        buf.extend_from_slice(value);
+
+        let cache = parse_cache(&buf);
+        assert_eq!(
+            cache.get("libz.so.1"),
+            Some(&vec![PathBuf::from("/usr/lib/libz.so.1")])
+        );
+    }
+}
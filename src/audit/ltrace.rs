@@ -0,0 +1,123 @@
+//! ltrace text log parser. Library calls are normalized onto the syscall they
+//! end up making so the trace audit can treat them like strace events:
+//! `fopen("/x", "w")` becomes `open("/x", O_WRONLY)`, `SYS_openat(...)` (from
+//! `ltrace -S`) becomes `openat(...)`.
+
+use super::SyscallEvent;
+use regex::Regex;
+
+pub fn parse(log: &str) -> Vec<SyscallEvent> {
+    let line_re = Regex::new(
+        r"^(?:\[pid\s+(\d+)\]\s+|(\d+)\s+)?(?:[\d:.]+\s+)?(?:SYS_)?([\w@.]+)\((.*)\)\s+=\s+(\S+)",
+    )
+    .unwrap();
+    let quoted_re = Regex::new(r#""((?:[^"\\]|\\.)*)""#).unwrap();
+
+    let mut events = Vec::new();
+    for line in log.lines() {
+        let Some(c) = line_re.captures(line.trim()) else {
+            continue;
+        };
+        let pid = c
+            .get(1)
+            .or_else(|| c.get(2))
+            .and_then(|m| m.as_str().parse().ok());
+        // libc symbols may carry a library suffix ("fopen@libc.so.6")
+        let name = c[3].split('@').next().unwrap_or_default();
+        let args = &c[4];
+        let ret = parse_ret(&c[5]);
+        let quoted: Vec<&str> = quoted_re
+            .captures_iter(args)
+            .filter_map(|q| q.get(1).map(|m| m.as_str()))
+            .collect();
+
+        let ev = match name {
+            "fopen" | "fopen64" | "freopen" | "freopen64" => {
+                let Some(path) = quoted.first() else {
+                    continue;
+                };
+                let mode = quoted.get(1).copied().unwrap_or("r");
+                let flags = if mode.contains(['w', 'a', '+']) {
+                    "O_WRONLY|O_CREAT"
+                } else {
+                    "O_RDONLY"
+                };
+                SyscallEvent {
+                    pid,
+                    name: "open".to_string(),
+                    args: format!("\"{path}\", {flags}"),
+                    // fopen returns a FILE*, NULL on failure; the fd itself is unknown
+                    ret: ret.map(|r| if r == 0 { -1 } else { 0 }),
+                }
+            }
+            "open64" | "__open" | "__open64" => SyscallEvent {
+                pid,
+                name: "open".to_string(),
+                args: args.to_string(),
+                ret,
+            },
+            "getaddrinfo" => {
+                let Some(host) = quoted.first() else {
+                    continue;
+                };
+                let args = match quoted.get(1) {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host.to_string(),
+                };
+                SyscallEvent {
+                    pid,
+                    name: "getaddrinfo".to_string(),
+                    args,
+                    // getaddrinfo returns 0 on success, EAI_* (non-zero) on failure
+                    ret: ret.map(|r| if r == 0 { 0 } else { -1 }),
+                }
+            }
+            _ => SyscallEvent {
+                pid,
+                name: name.to_string(),
+                args: args.to_string(),
+                ret,
+            },
+        };
+        events.push(ev);
+    }
+    events
+}
+
+/// ltrace prints pointers as hex and NULL as `0` or `nil`.
+fn parse_ret(s: &str) -> Option<i64> {
+    if s == "nil" {
+        return Some(0);
+    }
+    match s.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok().or(Some(i64::MAX)),
+        None => s.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_fopen_getaddrinfo_and_syscalls() {
+        let log = r#"fopen("/etc/app.conf", "r") = 0x5581a2b0
+[pid 7] fopen("/var/log/app.log", "a") = 0x5581a400
+fopen("/missing", "r") = 0
+getaddrinfo("api.example.com", "443", 0x7ffd, 0x7ffe) = 0
+SYS_openat(-100, "/etc/hosts", 524288) = 3
+"#;
+        let ev = parse(log);
+        assert_eq!(ev.len(), 5);
+        assert_eq!(ev[0].name, "open");
+        assert_eq!(ev[0].args, "\"/etc/app.conf\", O_RDONLY");
+        assert_eq!(ev[0].ret, Some(0));
+        assert_eq!(ev[1].pid, Some(7));
+        assert!(ev[1].args.contains("O_WRONLY"));
+        assert_eq!(ev[2].ret, Some(-1));
+        assert_eq!(ev[3].name, "getaddrinfo");
+        assert_eq!(ev[3].args, "api.example.com:443");
+        assert_eq!(ev[4].name, "openat");
+        assert_eq!(ev[4].ret, Some(3));
+    }
+}
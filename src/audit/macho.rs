@@ -0,0 +1,260 @@
+//! Mach-O audit: header hardening flags, hardened runtime from the code signature,
+//! linked dylibs, and network intent from libSystem socket imports or networking
+//! frameworks. Fat binaries are audited per architecture slice.
+
+use super::{extract_ascii_strings, hosts_from_urls, suggested_manifest, write_manifest, yesno};
+use crate::json::Json;
+use anyhow::{Context, Result, anyhow};
+use goblin::mach::{
+    Mach, MachO,
+    header::{MH_ALLOW_STACK_EXECUTION, MH_NO_HEAP_EXECUTION, MH_PIE},
+    load_command::CommandVariant,
+};
+use regex::Regex;
+use std::{collections::BTreeSet, fs, path::Path};
+
+const NET_SYMBOLS: &[&str] = &["_socket", "_connect", "_getaddrinfo", "_sendto"];
+const NET_FRAMEWORKS: &[&str] = &["CFNetwork.framework", "Network.framework"];
+
+/// CodeDirectory flag set by `codesign --options runtime`.
+const CS_RUNTIME: u32 = 0x0001_0000;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Flags {
+    pie: bool,
+    nx_stack: bool,
+    nx_heap: bool,
+    signed: bool,
+    hardened_runtime: bool,
+}
+
+/// Read the CodeDirectory flags out of the embedded signature superblob
+/// (big-endian: magic, length, count, then `count` (type, offset) entries).
+fn code_directory_flags(sig: &[u8]) -> Option<u32> {
+    let be = |at: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(sig.get(at..at + 4)?.try_into().ok()?))
+    };
+    if be(0)? != 0xfade_0cc0 {
+        return None;
+    }
+    let count = be(8)? as usize;
+    (0..count.min(64)).find_map(|i| {
+        let (kind, offset) = (be(12 + i * 8)?, be(16 + i * 8)? as usize);
+        // slot 0 is the CodeDirectory; its flags sit after magic, length, version
+        (kind == 0 && be(offset)? == 0xfade_0c02)
+            .then(|| be(offset + 12))
+            .flatten()
+    })
+}
+
+fn flags(macho: &MachO, buf: &[u8]) -> Flags {
+    let sig = macho.load_commands.iter().find_map(|lc| match &lc.command {
+        CommandVariant::CodeSignature(cs) => {
+            let start = cs.dataoff as usize;
+            buf.get(start..start + cs.datasize as usize)
+        }
+        _ => None,
+    });
+    let cs_flags = sig.and_then(code_directory_flags);
+    Flags {
+        pie: macho.header.flags & MH_PIE != 0,
+        nx_stack: macho.header.flags & MH_ALLOW_STACK_EXECUTION == 0,
+        nx_heap: macho.header.flags & MH_NO_HEAP_EXECUTION != 0,
+        signed: sig.is_some(),
+        hardened_runtime: cs_flags.is_some_and(|f| f & CS_RUNTIME != 0),
+    }
+}
+
+fn net_evidence(libs: &[&str], imports: &BTreeSet<String>) -> Vec<String> {
+    let mut out: Vec<String> = libs
+        .iter()
+        .filter(|l| NET_FRAMEWORKS.iter().any(|f| l.contains(f)))
+        .map(|l| format!("links {l}"))
+        .collect();
+    if imports.iter().any(|i| NET_SYMBOLS.contains(&i.as_str())) {
+        out.push("libSystem socket/DNS imports".to_string());
+    }
+    out
+}
+
+/// Each architecture with the bytes it was parsed from. Load command offsets such as
+/// `LC_CODE_SIGNATURE.dataoff` are relative to the slice, not the fat file.
+fn slices(buf: &[u8]) -> Result<Vec<(MachO<'_>, &[u8])>> {
+    Ok(
+        match Mach::parse(buf).map_err(|e| anyhow!("not a valid Mach-O: {e}"))? {
+            Mach::Binary(m) => vec![(m, buf)],
+            Mach::Fat(fat) => fat
+                .iter_arches()
+                .filter_map(|arch| {
+                    let bytes = arch.ok()?.slice(buf);
+                    Some((MachO::parse(bytes, 0).ok()?, bytes))
+                })
+                .collect(),
+        },
+    )
+}
+
+pub fn audit(
+    path: &Path,
+    buf: &[u8],
+    json: Option<&Path>,
+    manifest_out: Option<&Path>,
+) -> Result<()> {
+    let slices = slices(buf)?;
+
+    let strings = extract_ascii_strings(buf, 4);
+    let path_re = Regex::new(r#"(/(?:etc|var|usr|Library|Users|private)/[^\s"']+)"#).unwrap();
+    let paths: BTreeSet<String> = strings
+        .iter()
+        .filter_map(|s| path_re.captures(s).map(|c| c[1].to_string()))
+        .collect();
+    let url_hosts = hosts_from_urls(&strings);
+
+    println!("== Mach-O Audit ==");
+    println!("File: {}", path.display());
+    let mut net = Vec::new();
+    let mut arches = Vec::new();
+    for (m, bytes) in &slices {
+        let f = flags(m, bytes);
+        let imports: BTreeSet<String> = m
+            .imports()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| i.name.to_string())
+            .collect();
+        println!("\nArch (cputype): {:#x}", m.header.cputype);
+        println!("PIE              : {}", yesno(f.pie));
+        println!("NX stack         : {}", yesno(f.nx_stack));
+        println!("NX heap          : {}", yesno(f.nx_heap));
+        println!("Code signature   : {}", yesno(f.signed));
+        println!("Hardened runtime : {}", yesno(f.hardened_runtime));
+        if !m.libs.is_empty() {
+            println!("Linked dylibs:");
+            for l in m.libs.iter().filter(|l| **l != "self") {
+                println!("  - {}", l);
+            }
+        }
+        net.extend(net_evidence(&m.libs, &imports));
+        arches.push(
+            Json::obj()
+                .with("cputype", u64::from(m.header.cputype))
+                .with("pie", f.pie)
+                .with("nx_stack", f.nx_stack)
+                .with("nx_heap", f.nx_heap)
+                .with("signed", f.signed)
+                .with("hardened_runtime", f.hardened_runtime)
+                .with(
+                    "libs",
+                    m.libs.iter().map(|l| l.to_string()).collect::<Vec<_>>(),
+                )
+                .with("imports", imports.into_iter().collect::<Vec<_>>()),
+        );
+    }
+    net.sort();
+    net.dedup();
+    if !url_hosts.is_empty() {
+        net.push("embedded http(s) URLs".to_string());
+    }
+
+    if !paths.is_empty() {
+        println!("\nCandidate config/data paths (from strings):");
+        for p in &paths {
+            println!("  - {}", p);
+        }
+    }
+    let net_intent = !net.is_empty();
+    println!("\nNetwork capability required: {}", yesno(net_intent));
+    for e in &net {
+        println!("  - {}", e);
+    }
+
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("app");
+    let hosts = net_intent.then_some(url_hosts.clone());
+    let suggested = suggested_manifest(name, &paths, hosts.as_ref());
+    println!("\n== Suggested manifest (skeleton) ==");
+    println!("# TODO: adjust capabilities.memory.max_bytes");
+    print!("{}", suggested);
+    if let Some(out) = manifest_out {
+        write_manifest(out, &suggested)?;
+    }
+
+    if let Some(j) = json {
+        let report = Json::obj()
+            .with("file", path.display().to_string())
+            .with("format", "mach-o")
+            .with("arches", arches)
+            .with("paths", paths.into_iter().collect::<Vec<_>>())
+            .with("network", net_intent)
+            .with("network_evidence", net)
+            .with("url_hosts", url_hosts.into_iter().collect::<Vec<_>>());
+        fs::write(j, format!("{report}\n"))
+            .with_context(|| format!("failed to write {}", j.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_hardened_runtime_from_code_directory() {
+        let mut sig = Vec::new();
+        for w in [0xfade_0cc0u32, 0, 1, 0, 20] {
+            sig.extend_from_slice(&w.to_be_bytes());
+        }
+        // CodeDirectory: magic, length, version, flags
+        for w in [0xfade_0c02u32, 0, 0x20400, CS_RUNTIME] {
+            sig.extend_from_slice(&w.to_be_bytes());
+        }
+        assert_eq!(code_directory_flags(&sig), Some(CS_RUNTIME));
+        assert_eq!(code_directory_flags(&sig[..10]), None);
+        assert_eq!(code_directory_flags(&[0; 32]), None);
+    }
+
+    fn signature() -> Vec<u8> {
+        let mut sig = Vec::new();
+        for w in [
+            0xfade_0cc0u32,
+            0,
+            1,
+            0,
+            20,
+            0xfade_0c02,
+            0,
+            0x20400,
+            CS_RUNTIME,
+        ] {
+            sig.extend_from_slice(&w.to_be_bytes());
+        }
+        sig
+    }
+
+    #[test]
+    fn fat_slices_read_their_own_code_signature() {
+        // thin x86_64 image: header, one LC_CODE_SIGNATURE, then the signature at 48
+        let sig = signature();
+        let mut thin = Vec::new();
+        for w in [0xfeed_facfu32, 0x0100_0007, 3, 2, 1, 16, MH_PIE, 0] {
+            thin.extend_from_slice(&w.to_le_bytes());
+        }
+        for w in [0x1du32, 16, 48, sig.len() as u32] {
+            thin.extend_from_slice(&w.to_le_bytes());
+        }
+        thin.extend_from_slice(&sig);
+
+        // fat wrapper placing the slice at 64, so file and slice offsets disagree
+        let mut fat = Vec::new();
+        for w in [0xcafe_babeu32, 1, 0x0100_0007, 3, 64, thin.len() as u32, 0] {
+            fat.extend_from_slice(&w.to_be_bytes());
+        }
+        fat.resize(64, 0);
+        fat.extend_from_slice(&thin);
+
+        let slices = slices(&fat).unwrap();
+        assert_eq!(slices.len(), 1);
+        let (m, bytes) = &slices[0];
+        let f = flags(m, bytes);
+        assert!(f.pie && f.signed && f.hardened_runtime, "{f:?}");
+    }
+}
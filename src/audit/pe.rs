@@ -0,0 +1,160 @@
+//! Windows PE audit: mitigation flags from the optional header, imported DLLs, and
+//! network intent from the Winsock/WinINet/WinHTTP families.
+
+use super::{extract_ascii_strings, hosts_from_urls, suggested_manifest, write_manifest, yesno};
+use crate::json::Json;
+use anyhow::{Context, Result};
+use goblin::pe::{PE, dll_characteristic::*};
+use regex::Regex;
+use std::{collections::BTreeSet, fs, path::Path};
+
+const NET_DLLS: &[&str] = &[
+    "ws2_32.dll",
+    "wsock32.dll",
+    "wininet.dll",
+    "winhttp.dll",
+    "dnsapi.dll",
+];
+
+/// Mitigations advertised by `DllCharacteristics`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Mitigations {
+    aslr: bool,
+    high_entropy_va: bool,
+    dep: bool,
+    cfg: bool,
+}
+
+impl Mitigations {
+    fn from_characteristics(c: u16) -> Self {
+        Mitigations {
+            aslr: c & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0,
+            high_entropy_va: c & IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA != 0,
+            dep: c & IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0,
+            cfg: c & IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0,
+        }
+    }
+}
+
+fn net_dlls(libraries: &[&str]) -> Vec<String> {
+    libraries
+        .iter()
+        .map(|l| l.to_ascii_lowercase())
+        .filter(|l| NET_DLLS.contains(&l.as_str()))
+        .collect()
+}
+
+pub fn audit(
+    path: &Path,
+    buf: &[u8],
+    pe: &PE,
+    json: Option<&Path>,
+    manifest_out: Option<&Path>,
+) -> Result<()> {
+    let characteristics = pe
+        .header
+        .optional_header
+        .map(|o| o.windows_fields.dll_characteristics)
+        .unwrap_or(0);
+    let m = Mitigations::from_characteristics(characteristics);
+    let imports: BTreeSet<String> = pe
+        .imports
+        .iter()
+        .map(|i| format!("{}!{}", i.dll, i.name))
+        .collect();
+    let net = net_dlls(&pe.libraries);
+
+    let strings = extract_ascii_strings(buf, 4);
+    let path_re = Regex::new(r#"([A-Za-z]:\\[^\s"'<>|*?]+)"#).unwrap();
+    let paths: BTreeSet<String> = strings
+        .iter()
+        .filter_map(|s| path_re.captures(s).map(|c| c[1].to_string()))
+        .collect();
+    let url_hosts = hosts_from_urls(&strings);
+
+    println!("== PE Audit ==");
+    println!("File: {}", path.display());
+    println!(
+        "Arch: {:#06x} ({})",
+        pe.header.coff_header.machine,
+        if pe.is_64 { "PE32+" } else { "PE32" }
+    );
+    println!("ASLR (DYNAMIC_BASE): {}", yesno(m.aslr));
+    println!("High-entropy VA    : {}", yesno(m.high_entropy_va));
+    println!("DEP (NX_COMPAT)    : {}", yesno(m.dep));
+    println!("Control Flow Guard : {}", yesno(m.cfg));
+
+    if !pe.libraries.is_empty() {
+        println!("\nImported DLLs:");
+        for l in &pe.libraries {
+            println!("  - {}", l);
+        }
+    }
+    if !paths.is_empty() {
+        println!("\nCandidate paths (from strings):");
+        for p in &paths {
+            println!("  - {}", p);
+        }
+    }
+    let net_intent = !net.is_empty() || !url_hosts.is_empty();
+    println!("\nNetwork capability required: {}", yesno(net_intent));
+    for d in &net {
+        println!("  - imports {}", d);
+    }
+
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("app");
+    let hosts = net_intent.then_some(url_hosts.clone());
+    let suggested = suggested_manifest(name, &BTreeSet::new(), hosts.as_ref());
+    println!("\n== Suggested manifest (skeleton) ==");
+    println!("# TODO: Windows paths cannot be expressed as capabilities.files yet");
+    print!("{}", suggested);
+    if let Some(out) = manifest_out {
+        write_manifest(out, &suggested)?;
+    }
+
+    if let Some(j) = json {
+        let report = Json::obj()
+            .with("file", path.display().to_string())
+            .with("format", "pe")
+            .with("machine", u64::from(pe.header.coff_header.machine))
+            .with("is_64", pe.is_64)
+            .with(
+                "hardening",
+                Json::obj()
+                    .with("aslr", m.aslr)
+                    .with("high_entropy_va", m.high_entropy_va)
+                    .with("dep", m.dep)
+                    .with("cfg", m.cfg),
+            )
+            .with(
+                "libraries",
+                pe.libraries
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .with("imports", imports.into_iter().collect::<Vec<_>>())
+            .with("paths", paths.into_iter().collect::<Vec<_>>())
+            .with("network", net_intent)
+            .with("url_hosts", url_hosts.into_iter().collect::<Vec<_>>());
+        fs::write(j, format!("{report}\n"))
+            .with_context(|| format!("failed to write {}", j.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_mitigations_and_network_dlls() {
+        let m = Mitigations::from_characteristics(0x0140 | 0x4000);
+        assert!(m.aslr && m.dep && m.cfg);
+        assert!(!m.high_entropy_va);
+        assert_eq!(
+            net_dlls(&["KERNEL32.dll", "WS2_32.dll", "WinHttp.dll"]),
+            ["ws2_32.dll", "winhttp.dll"]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! `perf trace` text output parser, e.g.
+//! `0.123 ( 0.004 ms): curl/1234 openat(dfd: CWD, filename: "/etc/hosts", flags: RDONLY|CLOEXEC) = 3`.
+//! Flag names lack the `O_` prefix, so it is restored to match strace rendering.
+
+use super::SyscallEvent;
+use regex::Regex;
+
+pub fn parse(log: &str) -> Vec<SyscallEvent> {
+    let line_re = Regex::new(
+        r"^\s*[\d.]+\s+\(\s*[\d.]+\s+ms\):\s+(?:.*?/(\d+)\s+)?(\w+)\((.*)\)\s+=\s+(-?\d+)",
+    )
+    .unwrap();
+    let flags_re = Regex::new(r"flags:\s*([A-Z_|]+)").unwrap();
+
+    let mut events = Vec::new();
+    for line in log.lines() {
+        let Some(c) = line_re.captures(line) else {
+            continue;
+        };
+        let args = flags_re
+            .replace(&c[3], |f: &regex::Captures| {
+                let flags: Vec<String> = f[1].split('|').map(|x| format!("O_{x}")).collect();
+                format!("flags: {}", flags.join("|"))
+            })
+            .into_owned();
+        events.push(SyscallEvent {
+            pid: c.get(1).and_then(|m| m.as_str().parse().ok()),
+            name: c[2].to_string(),
+            args,
+            ret: c[4].parse().ok(),
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_perf_trace_lines() {
+        let log = r#"     0.000 ( 0.004 ms): curl/4242 openat(dfd: CWD, filename: "/etc/hosts", flags: RDONLY|CLOEXEC) = 3
+     0.010 ( 0.002 ms): curl/4242 openat(dfd: CWD, filename: "/tmp/out", flags: WRONLY|CREAT) = 4
+     0.020 ( 0.001 ms): curl/4243 openat(dfd: CWD, filename: "/nope") = -1 ENOENT (No such file or directory)
+"#;
+        let ev = parse(log);
+        assert_eq!(ev.len(), 3);
+        assert_eq!(ev[0].pid, Some(4242));
+        assert!(ev[0].args.contains("flags: O_RDONLY|O_CLOEXEC"));
+        assert!(ev[1].args.contains("O_WRONLY|O_CREAT"));
+        assert_eq!(ev[2].ret, Some(-1));
+    }
+}
@@ -0,0 +1,123 @@
+//! strace text log parser. Handles `-f` PID prefixes (`[pid N]` or a bare leading
+//! PID with `-o`), optional `-t/-tt/-ttt` timestamps, and joins
+//! `<unfinished ...>` / `<... resumed>` pairs back into a single event.
+
+use super::SyscallEvent;
+use regex::Regex;
+use std::collections::HashMap;
+
+pub fn parse(log: &str) -> Vec<SyscallEvent> {
+    let prefix_re = Regex::new(
+        r"^(?:\[pid\s+(\d+)\]\s+|(\d+)\s+)?(?:\d{2}:\d{2}:\d{2}(?:\.\d+)?\s+|\d+\.\d+\s+)?(.*)$",
+    )
+    .unwrap();
+    let unfinished_re = Regex::new(r"^(\w+)\((.*?)\s*<unfinished \.\.\.>$").unwrap();
+    let resumed_re = Regex::new(r"^<\.\.\. (\w+) resumed>\s*(.*)$").unwrap();
+
+    let mut events = Vec::new();
+    // (pid, syscall) -> args seen so far
+    let mut pending: HashMap<(Option<u32>, String), String> = HashMap::new();
+
+    for line in log.lines() {
+        let Some(c) = prefix_re.captures(line.trim()) else {
+            continue;
+        };
+        let pid = c
+            .get(1)
+            .or_else(|| c.get(2))
+            .and_then(|m| m.as_str().parse().ok());
+        let body = c.get(3).map_or("", |m| m.as_str());
+
+        // signals and exit notices
+        if body.starts_with("---") || body.starts_with("+++") {
+            continue;
+        }
+
+        if let Some(u) = unfinished_re.captures(body) {
+            pending.insert((pid, u[1].to_string()), u[2].to_string());
+            continue;
+        }
+
+        let joined;
+        let body = if let Some(r) = resumed_re.captures(body) {
+            let head = pending.remove(&(pid, r[1].to_string())).unwrap_or_default();
+            joined = format!("{}({}{}", &r[1], head, &r[2]);
+            joined.as_str()
+        } else {
+            body
+        };
+
+        if let Some(ev) = parse_call(pid, body) {
+            events.push(ev);
+        }
+    }
+
+    events
+}
+
+/// Split `name(args) = ret ...` where args may itself contain parentheses and `=`.
+fn parse_call(pid: Option<u32>, body: &str) -> Option<SyscallEvent> {
+    let open = body.find('(')?;
+    let name = &body[..open];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let (args, ret) = match body.rfind(") = ") {
+        Some(i) => (&body[open + 1..i], Some(&body[i + 4..])),
+        None => (body[open + 1..].trim_end_matches(')'), None),
+    };
+    Some(SyscallEvent {
+        pid,
+        name: name.to_string(),
+        args: args.to_string(),
+        ret: ret
+            .and_then(|r| r.split_whitespace().next())
+            .and_then(|r| r.parse().ok()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_pid_prefixed_lines() {
+        let log = r#"openat(AT_FDCWD, "/etc/hosts", O_RDONLY) = 3
+[pid  4242] openat(AT_FDCWD, "/etc/a b", O_RDONLY) = 4
+4243  12:00:00.123456 close(4) = 0
+--- SIGCHLD {si_signo=SIGCHLD} ---
++++ exited with 0 +++
+"#;
+        let ev = parse(log);
+        assert_eq!(ev.len(), 3);
+        assert_eq!(ev[0].pid, None);
+        assert_eq!(ev[0].ret, Some(3));
+        assert_eq!(ev[1].pid, Some(4242));
+        assert_eq!(ev[1].args, r#"AT_FDCWD, "/etc/a b", O_RDONLY"#);
+        assert_eq!(ev[2].pid, Some(4243));
+        assert_eq!(ev[2].name, "close");
+    }
+
+    #[test]
+    fn joins_unfinished_and_resumed() {
+        let log = r#"[pid 10] connect(3, {sa_family=AF_INET, sin_port=htons(443), sin_addr=inet_addr("1.2.3.4")}, 16 <unfinished ...>
+[pid 11] openat(AT_FDCWD, "/etc/x", O_RDONLY) = 5
+[pid 10] <... connect resumed>) = 0
+"#;
+        let ev = parse(log);
+        assert_eq!(ev.len(), 2);
+        let connect = ev.iter().find(|e| e.name == "connect").unwrap();
+        assert_eq!(connect.pid, Some(10));
+        assert!(connect.args.contains("inet_addr(\"1.2.3.4\")"));
+        assert!(connect.args.ends_with("16"));
+        assert_eq!(connect.ret, Some(0));
+    }
+
+    #[test]
+    fn failed_calls_keep_negative_return() {
+        let ev = parse(
+            "openat(AT_FDCWD, \"/nope\", O_RDONLY) = -1 ENOENT (No such file or directory)\n",
+        );
+        assert_eq!(ev[0].ret, Some(-1));
+    }
+}
@@ -0,0 +1,541 @@
+use crate::manifest::{self, Manifest, RootFs};
+use anyhow::{Context, Result};
+use std::{collections::BTreeSet, io::IsTerminal, path::Path};
+
+/// A single field-level difference between two manifests.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Change {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    /// The new manifest asks for more than the old one did.
+    pub escalation: bool,
+}
+
+pub fn diff<P: AsRef<Path>>(old: P, new: P) -> Result<()> {
+    let a = load(old.as_ref())?;
+    let b = load(new.as_ref())?;
+    let changes = diff_manifests(&a, &b);
+
+    println!("== Manifest Diff ==");
+    println!("Old: {}", old.as_ref().display());
+    println!("New: {}", new.as_ref().display());
+
+    if changes.is_empty() {
+        println!("\nNo differences");
+        return Ok(());
+    }
+
+    let color = std::io::stdout().is_terminal();
+    println!();
+    for c in &changes {
+        let line = match (&c.old, &c.new) {
+            (Some(o), Some(n)) => format!("~ {}: {} -> {}", c.field, o, n),
+            (None, Some(n)) => format!("+ {}: {}", c.field, n),
+            (Some(o), None) => format!("- {}: {}", c.field, o),
+            (None, None) => continue,
+        };
+        if c.escalation && color {
+            println!("\x1b[31m{line}\x1b[0m");
+        } else {
+            println!("{line}");
+        }
+    }
+
+    let escalations = changes.iter().filter(|c| c.escalation).count();
+    println!("\nPrivilege escalations: {}", escalations);
+
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Manifest> {
+    manifest::load_manifest(path).with_context(|| format!("in {}", path.display()))
+}
+
+/// Compare two manifests field by field. Capability lists are compared as sets.
+/// Each `[profile.*]` is compared as merged onto its base; only changes the base
+/// comparison does not already show are reported, under `profile.<name>.`.
+pub fn diff_manifests(old: &Manifest, new: &Manifest) -> Vec<Change> {
+    let mut out = Vec::new();
+
+    scalar(&mut out, "name", Some(&old.name), Some(&new.name), false);
+    scalar(
+        &mut out,
+        "version",
+        Some(&old.version),
+        Some(&new.version),
+        false,
+    );
+    let base = compare(old, new);
+
+    let names: BTreeSet<&String> = old.profile.keys().chain(new.profile.keys()).collect();
+    let mut profiles = Vec::new();
+    for name in names {
+        // a manifest without the profile runs with its base capabilities
+        let merged = |m: &Manifest| m.with_profile(name).unwrap_or_else(|_| m.clone());
+        for mut c in compare(&merged(old), &merged(new)) {
+            if !base.contains(&c) {
+                c.field = format!("profile.{name}.{}", c.field);
+                profiles.push(c);
+            }
+        }
+    }
+    out.extend(base);
+    out.extend(profiles);
+    out
+}
+
+/// Everything except name and version.
+fn compare(old: &Manifest, new: &Manifest) -> Vec<Change> {
+    let mut out = Vec::new();
+
+    let old_mem = old.capabilities.memory.as_ref().map(|m| m.max_bytes);
+    let new_mem = new.capabilities.memory.as_ref().map(|m| m.max_bytes);
+    let mem_up = match (old_mem, new_mem) {
+        (Some(o), Some(n)) => n > o,
+        (Some(_), None) => true, // dropping the limit means unbounded
+        _ => false,
+    };
+    scalar(
+        &mut out,
+        "capabilities.memory.max_bytes",
+        old_mem.as_ref(),
+        new_mem.as_ref(),
+        mem_up,
+    );
+
+    let old_tmp = tmp_max_bytes(old);
+    let new_tmp = tmp_max_bytes(new);
+    let tmp_up = match (old_tmp, new_tmp) {
+        (Some(o), Some(n)) => n > o,
+        (None, Some(_)) => true, // a writable /tmp where there was none
+        _ => false,
+    };
+    scalar(
+        &mut out,
+        "capabilities.files.tmp.max_bytes",
+        old_tmp.as_ref(),
+        new_tmp.as_ref(),
+        tmp_up,
+    );
+
+    list(
+        &mut out,
+        "capabilities.files.read.paths",
+        read_paths(old),
+        read_paths(new),
+        true,
+    );
+    // dropping a deny widens access, adding one narrows it
+    list(
+        &mut out,
+        "capabilities.files.deny_read",
+        deny_paths(old),
+        deny_paths(new),
+        false,
+    );
+    list(
+        &mut out,
+        "capabilities.network.connect.hosts",
+        connect_hosts(old),
+        connect_hosts(new),
+        true,
+    );
+    let (old_ports, new_ports) = (listen_ports(old), listen_ports(new));
+    list(
+        &mut out,
+        "capabilities.network.listen.ports",
+        old_ports.iter().map(String::as_str).collect(),
+        new_ports.iter().map(String::as_str).collect(),
+        true,
+    );
+    // an empty list binds every interface, so it shows up as `*`
+    list(
+        &mut out,
+        "capabilities.network.listen.interfaces",
+        listen_interfaces(old),
+        listen_interfaces(new),
+        true,
+    );
+    list(
+        &mut out,
+        "capabilities.sockets.unix.paths",
+        unix_sockets(old),
+        unix_sockets(new),
+        true,
+    );
+    list(
+        &mut out,
+        "capabilities.syscalls.allow",
+        allowed_syscalls(old),
+        allowed_syscalls(new),
+        true,
+    );
+    list(
+        &mut out,
+        "capabilities.devices.paths",
+        device_paths(old),
+        device_paths(new),
+        true,
+    );
+    list(
+        &mut out,
+        "capabilities.ipc",
+        ipc_grants(old),
+        ipc_grants(new),
+        true,
+    );
+    let rootfs = |m: &Manifest| {
+        let readonly = m.runtime.as_ref().and_then(|r| r.rootfs) == Some(RootFs::Readonly);
+        if readonly { "readonly" } else { "host" }
+    };
+    let (old_root, new_root) = (rootfs(old), rootfs(new));
+    scalar(
+        &mut out,
+        "runtime.rootfs",
+        Some(&old_root),
+        Some(&new_root),
+        new_root == "host",
+    );
+    let flags = |m: &Manifest| {
+        m.runtime
+            .as_ref()
+            .map_or((false, false), |r| (r.allow_realtime, r.allow_clock_set))
+    };
+    let ((old_rt, old_clock), (new_rt, new_clock)) = (flags(old), flags(new));
+    scalar(
+        &mut out,
+        "runtime.allow_realtime",
+        Some(&old_rt),
+        Some(&new_rt),
+        new_rt,
+    );
+    scalar(
+        &mut out,
+        "runtime.allow_clock_set",
+        Some(&old_clock),
+        Some(&new_clock),
+        new_clock,
+    );
+    list(
+        &mut out,
+        "runtime.retain_caps",
+        retained_caps(old),
+        retained_caps(new),
+        true,
+    );
+    // like memory: a higher limit, or dropping one back to the launcher's, loosens it
+    let limits = |m: &Manifest| m.runtime.as_ref().and_then(|r| r.limits.clone());
+    let (old_limits, new_limits) = (
+        limits(old).unwrap_or_default(),
+        limits(new).unwrap_or_default(),
+    );
+    for (field, o, n) in [
+        (
+            "runtime.limits.nofile",
+            old_limits.nofile,
+            new_limits.nofile,
+        ),
+        ("runtime.limits.nproc", old_limits.nproc, new_limits.nproc),
+        ("runtime.limits.fsize", old_limits.fsize, new_limits.fsize),
+        ("runtime.limits.core", old_limits.core, new_limits.core),
+    ] {
+        let up = match (o, n) {
+            (Some(o), Some(n)) => n > o,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        scalar(&mut out, field, o.as_ref(), n.as_ref(), up);
+    }
+
+    out
+}
+
+fn scalar<T: PartialEq + ToString>(
+    out: &mut Vec<Change>,
+    field: &str,
+    old: Option<&T>,
+    new: Option<&T>,
+    escalation: bool,
+) {
+    if old == new {
+        return;
+    }
+    out.push(Change {
+        field: field.to_string(),
+        old: old.map(|v| v.to_string()),
+        new: new.map(|v| v.to_string()),
+        escalation,
+    });
+}
+
+fn list(
+    out: &mut Vec<Change>,
+    field: &str,
+    old: BTreeSet<&str>,
+    new: BTreeSet<&str>,
+    grants: bool,
+) {
+    for added in new.difference(&old) {
+        out.push(Change {
+            field: field.to_string(),
+            old: None,
+            new: Some(added.to_string()),
+            escalation: grants,
+        });
+    }
+    for removed in old.difference(&new) {
+        out.push(Change {
+            field: field.to_string(),
+            old: Some(removed.to_string()),
+            new: None,
+            escalation: !grants,
+        });
+    }
+}
+
+fn read_paths(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .files
+        .as_ref()
+        .and_then(|f| f.read.as_ref())
+        .map(|r| r.paths.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn deny_paths(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .files
+        .as_ref()
+        .map(|f| f.deny_read.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn tmp_max_bytes(m: &Manifest) -> Option<u64> {
+    m.capabilities
+        .files
+        .as_ref()
+        .and_then(|f| f.tmp.as_ref())
+        .map(|t| t.max_bytes)
+}
+
+fn connect_hosts(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .network
+        .as_ref()
+        .and_then(|n| n.connect.as_ref())
+        .map(|c| c.hosts.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn allowed_syscalls(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .syscalls
+        .as_ref()
+        .map(|s| s.allow.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn device_paths(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .devices
+        .as_ref()
+        .map(|d| d.paths.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn ipc_grants(m: &Manifest) -> BTreeSet<&'static str> {
+    let ipc = m.capabilities.ipc.unwrap_or_default();
+    [("sysv", ipc.sysv), ("shm", ipc.shm)]
+        .into_iter()
+        .filter_map(|(k, on)| on.then_some(k))
+        .collect()
+}
+
+fn retained_caps(m: &Manifest) -> BTreeSet<&str> {
+    m.runtime
+        .as_ref()
+        .map(|r| r.retain_caps.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn unix_sockets(m: &Manifest) -> BTreeSet<&str> {
+    m.capabilities
+        .sockets
+        .as_ref()
+        .and_then(|s| s.unix.as_ref())
+        .map(|u| u.paths.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn listen_ports(m: &Manifest) -> BTreeSet<String> {
+    m.capabilities
+        .network
+        .as_ref()
+        .and_then(|n| n.listen.as_ref())
+        .map(|l| l.ports.iter().map(u16::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn listen_interfaces(m: &Manifest) -> BTreeSet<&str> {
+    let Some(l) = m
+        .capabilities
+        .network
+        .as_ref()
+        .and_then(|n| n.listen.as_ref())
+    else {
+        return BTreeSet::new();
+    };
+    if l.interfaces.is_empty() {
+        return BTreeSet::from(["*"]);
+    }
+    l.interfaces
+        .iter()
+        .map(|i| match i.as_str() {
+            "0.0.0.0" | "::" | "[::]" => "*",
+            i => i,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Manifest {
+        manifest::parse_manifest(s.as_bytes()).expect("valid manifest")
+    }
+
+    #[test]
+    fn identical_manifests_have_no_changes() {
+        let m = r#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.files.read]
+paths = ["/etc/demo"]
+"#;
+        assert!(diff_manifests(&parse(m), &parse(m)).is_empty());
+    }
+
+    #[test]
+    fn new_paths_hosts_and_more_memory_are_escalations() {
+        let old = parse(
+            r#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.memory]
+max_bytes = 1024
+
+[capabilities.files.read]
+paths = ["/etc/demo", "/etc/old"]
+"#,
+        );
+        let new = parse(
+            r#"
+name = "demo"
+version = "0.2.0"
+
+[capabilities.memory]
+max_bytes = 2048
+
+[capabilities.files.read]
+paths = ["/etc/demo", "/etc/shadow"]
+
+[capabilities.network.connect]
+hosts = ["evil.example.com:443"]
+"#,
+        );
+        let changes = diff_manifests(&old, &new);
+
+        let escalated: Vec<_> = changes
+            .iter()
+            .filter(|c| c.escalation)
+            .map(|c| c.field.as_str())
+            .collect();
+        assert_eq!(
+            escalated,
+            [
+                "capabilities.memory.max_bytes",
+                "capabilities.files.read.paths",
+                "capabilities.network.connect.hosts",
+            ]
+        );
+
+        let removed = changes
+            .iter()
+            .find(|c| c.new.is_none())
+            .expect("removed path");
+        assert_eq!(removed.old.as_deref(), Some("/etc/old"));
+        assert!(!removed.escalation);
+
+        let version = changes.iter().find(|c| c.field == "version").unwrap();
+        assert!(!version.escalation);
+    }
+
+    #[test]
+    fn profile_grants_are_diffed() {
+        let base = "name = \"demo\"\nversion = \"0.1.0\"\n";
+        let old = parse(base);
+        let new = parse(&format!(
+            "{base}[profile.dev.capabilities.files.read]\npaths = [\"/etc/shadow\"]\n"
+        ));
+        let changes = diff_manifests(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].field,
+            "profile.dev.capabilities.files.read.paths"
+        );
+        assert_eq!(changes[0].new.as_deref(), Some("/etc/shadow"));
+        assert!(changes[0].escalation);
+
+        // base changes are not repeated for profiles that inherit them
+        let both = "[capabilities.memory]\nmax_bytes = 4096\n\
+                    [profile.dev.capabilities.files.read]\npaths = [\"/etc/shadow\"]\n";
+        let newer = parse(&format!("{base}{both}"));
+        let fields: Vec<_> = diff_manifests(&new, &newer)
+            .into_iter()
+            .map(|c| c.field)
+            .collect();
+        assert_eq!(fields, ["capabilities.memory.max_bytes"]);
+    }
+
+    #[test]
+    fn raised_or_dropped_limits_are_escalations() {
+        let base = "name = \"demo\"\nversion = \"0.1.0\"\n";
+        let old = parse(&format!(
+            "{base}[runtime.limits]\nnofile = 256\nnproc = 64\nfsize = 1024\ncore = 0\n"
+        ));
+        let new = parse(&format!(
+            "{base}[runtime.limits]\nnofile = 1024\nnproc = 32\nfsize = 1024\n"
+        ));
+        let changes: Vec<_> = diff_manifests(&old, &new)
+            .into_iter()
+            .map(|c| (c.field, c.escalation))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("runtime.limits.nofile".to_string(), true),
+                ("runtime.limits.nproc".to_string(), false),
+                ("runtime.limits.core".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn listening_on_all_interfaces_is_an_escalation() {
+        let base =
+            "name = \"demo\"\nversion = \"0.1.0\"\n[capabilities.network.listen]\nports = [8080]\n";
+        let loopback = parse(&format!("{base}interfaces = [\"127.0.0.1\"]\n"));
+        for all in ["", "interfaces = [\"0.0.0.0\"]\n"] {
+            let changes = diff_manifests(&loopback, &parse(&format!("{base}{all}")));
+            let added = changes.iter().find(|c| c.new.is_some()).expect("added");
+            assert_eq!(added.field, "capabilities.network.listen.interfaces");
+            assert_eq!(added.new.as_deref(), Some("*"));
+            assert!(added.escalation);
+        }
+    }
+}
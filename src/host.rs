@@ -0,0 +1,238 @@
+//! Host entries of `[capabilities.network.connect]`.
+//!
+//! Accepted forms, each with an optional `:port` (IPv6 in brackets when a port follows):
+//! `api.example.com`, `*.example.com` (any subdomain, not the apex), `203.0.113.7`,
+//! `[2001:db8::1]`, `10.0.0.0/8`, `[2001:db8::/32]`.
+
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HostPattern {
+    Name {
+        name: String,
+        port: Option<u16>,
+    },
+    Wildcard {
+        suffix: String,
+        port: Option<u16>,
+    },
+    Ip {
+        addr: IpAddr,
+        port: Option<u16>,
+    },
+    Cidr {
+        net: IpAddr,
+        prefix: u8,
+        port: Option<u16>,
+    },
+}
+
+impl HostPattern {
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            HostPattern::Name { port, .. }
+            | HostPattern::Wildcard { port, .. }
+            | HostPattern::Ip { port, .. }
+            | HostPattern::Cidr { port, .. } => *port,
+        }
+    }
+
+    /// Whether a connection to `host` (a DNS name or IP literal) on `port` is covered.
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        if self.port().is_some_and(|p| p != port) {
+            return false;
+        }
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match self {
+            HostPattern::Name { name, .. } => name.eq_ignore_ascii_case(host),
+            HostPattern::Wildcard { suffix, .. } => {
+                let host = host.to_ascii_lowercase();
+                host.len() > suffix.len() + 1
+                    && host.ends_with(suffix.as_str())
+                    && host.as_bytes()[host.len() - suffix.len() - 1] == b'.'
+            }
+            HostPattern::Ip { addr, .. } => host.parse::<IpAddr>().is_ok_and(|ip| ip == *addr),
+            HostPattern::Cidr { net, prefix, .. } => host
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| in_cidr(ip, *net, *prefix)),
+        }
+    }
+}
+
+fn in_cidr(ip: IpAddr, net: IpAddr, prefix: u8) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(a), IpAddr::V4(n)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(a) & mask == u32::from(n) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(n)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(a) & mask == u128::from(n) & mask
+        }
+        _ => false,
+    }
+}
+
+impl FromStr for HostPattern {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, port) = split_port(s)?;
+        if host.is_empty() {
+            return Err("missing host");
+        }
+
+        if let Some((addr, prefix)) = host.split_once('/') {
+            let net: IpAddr = addr.parse().map_err(|_| "CIDR base is not an IP address")?;
+            let max = if net.is_ipv4() { 32 } else { 128 };
+            let prefix: u8 = prefix
+                .parse()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or("CIDR prefix length out of range")?;
+            return Ok(HostPattern::Cidr { net, prefix, port });
+        }
+        if let Ok(addr) = host.parse::<IpAddr>() {
+            return Ok(HostPattern::Ip { addr, port });
+        }
+        if let Some(suffix) = host.strip_prefix("*.") {
+            validate_dns_name(suffix)?;
+            return Ok(HostPattern::Wildcard {
+                suffix: suffix.to_ascii_lowercase(),
+                port,
+            });
+        }
+        if host.contains('*') {
+            return Err("wildcards are only allowed as the leftmost label (\"*.example.com\")");
+        }
+        validate_dns_name(host)?;
+        Ok(HostPattern::Name {
+            name: host.to_ascii_lowercase(),
+            port,
+        })
+    }
+}
+
+/// Split off an optional `:port`; bracketed hosts may contain colons.
+fn split_port(s: &str) -> Result<(&str, Option<u16>), &'static str> {
+    let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+        let (inner, after) = rest.split_once(']').ok_or("unclosed '['")?;
+        match after {
+            "" => (inner, None),
+            _ => (
+                inner,
+                Some(after.strip_prefix(':').ok_or("expected ':' after ']'")?),
+            ),
+        }
+    } else if s.matches(':').count() > 1 {
+        // bare IPv6 without port
+        (s, None)
+    } else {
+        match s.split_once(':') {
+            Some((h, p)) => (h, Some(p)),
+            None => (s, None),
+        }
+    };
+    let port = port
+        .map(|p| {
+            p.parse::<u16>()
+                .ok()
+                .filter(|p| *p != 0)
+                .ok_or("port must be 1-65535")
+        })
+        .transpose()?;
+    Ok((host, port))
+}
+
+fn validate_dns_name(name: &str) -> Result<(), &'static str> {
+    if name.len() > 253 {
+        return Err("host name longer than 253 characters");
+    }
+    let ok = name.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if ok {
+        Ok(())
+    } else {
+        Err("not a valid DNS name")
+    }
+}
+
+impl Display for HostPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (host, port) = match self {
+            HostPattern::Name { name, port } => (name.clone(), port),
+            HostPattern::Wildcard { suffix, port } => (format!("*.{suffix}"), port),
+            HostPattern::Ip { addr, port } => (addr.to_string(), port),
+            HostPattern::Cidr { net, prefix, port } => (format!("{net}/{prefix}"), port),
+        };
+        match (port, host.contains(':')) {
+            (Some(p), true) => write!(f, "[{host}]:{p}"),
+            (Some(p), false) => write!(f, "{host}:{p}"),
+            (None, _) => write!(f, "{host}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> HostPattern {
+        s.parse().unwrap_or_else(|e| panic!("{s:?}: {e}"))
+    }
+
+    #[test]
+    fn parses_and_round_trips_all_forms() {
+        for s in [
+            "api.example.com:443",
+            "*.example.com",
+            "203.0.113.7:80",
+            "[2001:db8::1]:443",
+            "2001:db8::1",
+            "10.0.0.0/8",
+            "[2001:db8::/32]:443",
+        ] {
+            assert_eq!(p(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        for s in [
+            "",
+            "api.*.com",
+            "foo*.example.com",
+            "-bad.example.com",
+            "host:0",
+            "host:99999",
+            "10.0.0.0/33",
+            "example.com/8",
+            "[::1",
+        ] {
+            assert!(
+                s.parse::<HostPattern>().is_err(),
+                "{s:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn matching_respects_wildcards_cidrs_and_ports() {
+        assert!(p("*.example.com:443").allows("api.example.com", 443));
+        assert!(!p("*.example.com:443").allows("example.com", 443));
+        assert!(!p("*.example.com:443").allows("api.example.com", 80));
+        assert!(!p("*.example.com").allows("evilexample.com", 443));
+        assert!(p("10.0.0.0/8").allows("10.1.2.3", 22));
+        assert!(!p("10.0.0.0/8").allows("11.0.0.1", 22));
+        assert!(p("[2001:db8::/32]:443").allows("[2001:db8::5]", 443));
+        assert!(p("API.example.com").allows("api.EXAMPLE.com", 1));
+    }
+}
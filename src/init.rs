@@ -0,0 +1,89 @@
+use crate::audit::audit_elf;
+use crate::manifest::{Capabilities, Manifest, Memory};
+use anyhow::{Context, Result, bail};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Commented-out sections appended to the template as a hint.
+const EXAMPLES: &str = r#"
+# [capabilities.files.read]
+# paths = ["/etc/app"]
+
+# [capabilities.network.connect]
+# hosts = ["api.example.com:443"]
+"#;
+
+/// Starting point for a hand-written manifest: a memory limit and commented examples.
+fn template(name: &str) -> String {
+    let manifest = Manifest {
+        name: name.to_string(),
+        version: "0.1.0".to_string(),
+        preset: None,
+        capabilities: Capabilities {
+            memory: Some(Memory {
+                max_bytes: 134_217_728, // 128 MiB
+            }),
+            ..Capabilities::default()
+        },
+        service: None,
+        runtime: None,
+        secrets: None,
+        profile: BTreeMap::new(),
+    };
+    format!("{manifest}{EXAMPLES}")
+}
+
+/// Scaffold `<name>.kpkg.toml` (or `output`). With `from_binary` the ELF audit runs
+/// and its suggested manifest is written instead of the template.
+pub fn init(from_binary: Option<&Path>, output: Option<&Path>, force: bool) -> Result<()> {
+    let name = match from_binary {
+        Some(b) => b.file_name().and_then(|s| s.to_str()).map(str::to_string),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|d| d.file_name().and_then(|s| s.to_str()).map(str::to_string)),
+    }
+    .unwrap_or_else(|| "app".to_string());
+    // a manifest name may not contain control characters
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+
+    let out = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(format!("{name}.kpkg.toml")));
+    if out.exists() && !force {
+        bail!(
+            "{} already exists; pass --force to overwrite",
+            out.display()
+        );
+    }
+
+    match from_binary {
+        Some(b) => audit_elf(b, None, Some(&out))?,
+        None => fs::write(&out, template(&name))
+            .with_context(|| format!("failed to write {}", out.display()))?,
+    }
+    println!("Created {}", out.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::parse_manifest;
+
+    #[test]
+    fn template_is_a_valid_manifest_and_init_refuses_to_overwrite() {
+        let m = parse_manifest(template("demo").as_bytes()).expect("valid template");
+        assert_eq!(m.name, "demo");
+        let quoted = parse_manifest(template("we\"ird\\name").as_bytes()).expect("valid template");
+        assert_eq!(quoted.name, "we\"ird\\name");
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("demo.kpkg.toml");
+        init(None, Some(&out), false).unwrap();
+        assert!(init(None, Some(&out), false).is_err());
+        init(None, Some(&out), true).unwrap();
+    }
+}
@@ -1,12 +1,28 @@
-use crate::manifest;
-use anyhow::{Context, Result};
-use std::{fs, path::Path};
+use crate::{manifest, policy};
+use anyhow::{Result, bail};
+use std::path::Path;
 
-pub fn inspect<P: AsRef<Path>>(path: P) -> Result<()> {
-    let bytes =
-        fs::read(&path).with_context(|| format!("failed to read {}", path.as_ref().display()))?;
-    let manifest = manifest::parse_manifest(&bytes)?;
+pub fn inspect<P: AsRef<Path>>(
+    path: P,
+    profile: Option<&str>,
+    policy_path: Option<&Path>,
+) -> Result<()> {
+    let mut manifest = manifest::load_manifest(path.as_ref())?;
     println!("Manifest is valid");
+    if let Some(p) = profile {
+        manifest = manifest.with_profile(p)?;
+        println!("Profile: {}", p);
+    }
     println!("\nManifest Content:\n{}\n", manifest);
+    if let Some(p) = policy_path {
+        let violations = policy::load_policy(p)?.violations(&manifest);
+        if !violations.is_empty() {
+            for v in &violations {
+                println!("policy violation: {v}");
+            }
+            bail!("manifest rejected by policy {}", p.display());
+        }
+        println!("Admitted by policy {}", p.display());
+    }
     Ok(())
 }
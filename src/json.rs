@@ -0,0 +1,145 @@
+//! Tiny JSON writer for reports. We only ever emit JSON, so this avoids pulling in serde_json.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Num(u64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(crate) fn obj() -> Self {
+        Json::Obj(Vec::new())
+    }
+
+    /// Append a key to an object; no-op on other variants.
+    pub(crate) fn with(mut self, key: &str, value: impl Into<Json>) -> Self {
+        if let Json::Obj(fields) = &mut self {
+            fields.push((key.to_string(), value.into()));
+        }
+        self
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Num(n) => write!(f, "{n}"),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(items) if items.is_empty() => f.write_str("[]"),
+            Json::Obj(fields) if fields.is_empty() => f.write_str("{}"),
+            Json::Arr(items) => {
+                f.write_str("[\n")?;
+                for (i, v) in items.iter().enumerate() {
+                    pad(f, indent + 1)?;
+                    v.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+                pad(f, indent)?;
+                f.write_char(']')
+            }
+            Json::Obj(fields) => {
+                f.write_str("{\n")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    pad(f, indent + 1)?;
+                    write_str(f, k)?;
+                    f.write_str(": ")?;
+                    v.write(f, indent + 1)?;
+                    f.write_str(if i + 1 < fields.len() { ",\n" } else { "\n" })?;
+                }
+                pad(f, indent)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn pad(f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+    for _ in 0..indent {
+        f.write_str("  ")?;
+    }
+    Ok(())
+}
+
+fn write_str(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Num(n)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::Str(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::Str(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::Arr(v.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_and_nests() {
+        let j = Json::obj()
+            .with("name", "a \"b\"\n")
+            .with("ok", true)
+            .with("n", 3u64)
+            .with("none", None::<String>)
+            .with("list", vec!["x"])
+            .with("empty", Vec::<String>::new());
+        assert_eq!(
+            j.to_string(),
+            "{\n  \"name\": \"a \\\"b\\\"\\n\",\n  \"ok\": true,\n  \"n\": 3,\n  \"none\": null,\n  \"list\": [\n    \"x\"\n  ],\n  \"empty\": []\n}"
+        );
+    }
+}
@@ -1,3 +1,11 @@
 pub mod audit;
+pub mod diff;
+pub mod host;
+pub mod init;
 pub mod inspect;
+mod json;
 pub mod manifest;
+pub mod policy;
+mod preset;
+pub mod report;
+pub mod systemd;
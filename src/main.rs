@@ -1,8 +1,12 @@
 #![forbid(unsafe_code)]
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
-use zerok::audit::{audit_elf, audit_trace};
+use zerok::audit::{TraceFormat, audit_elf, audit_trace};
+use zerok::diff::diff;
+use zerok::init::init;
 use zerok::inspect::inspect;
+use zerok::report::report;
+use zerok::systemd::systemd_unit;
 
 #[derive(Parser)]
 #[command(name = "zerok", version, author)]
@@ -13,11 +17,38 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Scaffold a manifest for a new package
+    Init(InitArgs),
+
     /// Validate a manifest file
     Inspect(InspectArgs),
 
     /// Audit binaries or traces to suggest a manifest
     Audit(AuditCmd),
+
+    /// Compare two manifests and highlight privilege escalations
+    Diff(DiffArgs),
+
+    /// Produce a reviewer-facing security report for a manifest
+    Report(ReportArgs),
+
+    /// Generate a hardened systemd service unit from a manifest
+    SystemdUnit(SystemdUnitArgs),
+}
+
+#[derive(Args)]
+struct InitArgs {
+    /// Pre-fill capabilities from an ELF audit of this binary
+    #[arg(long, value_name = "ELF_PATH")]
+    from_binary: Option<PathBuf>,
+
+    /// Where to write the manifest; defaults to `<name>.kpkg.toml`
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Overwrite an existing manifest
+    #[arg(long)]
+    force: bool,
 }
 
 #[derive(Args)]
@@ -25,6 +56,55 @@ struct InspectArgs {
     /// Path to the manifest to validate
     #[arg(value_name = "MANIFEST")]
     path: PathBuf,
+
+    /// Show the manifest with `[profile.<NAME>]` overrides applied
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Check the manifest against an admission policy (e.g. /etc/zerok/policy.toml)
+    #[arg(long, value_name = "POLICY")]
+    policy: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Baseline manifest
+    #[arg(value_name = "OLD")]
+    old: PathBuf,
+
+    /// Manifest to compare against the baseline
+    #[arg(value_name = "NEW")]
+    new: PathBuf,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    /// Manifest to report on
+    #[arg(value_name = "MANIFEST")]
+    path: PathBuf,
+
+    /// ELF binary the manifest is for; adds a hardening assessment
+    #[arg(long, value_name = "ELF_PATH")]
+    binary: Option<PathBuf>,
+
+    /// Write the report here (.json for JSON, otherwise Markdown); defaults to stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SystemdUnitArgs {
+    /// Manifest to translate
+    #[arg(value_name = "MANIFEST")]
+    path: PathBuf,
+
+    /// Command for ExecStart; defaults to /usr/local/bin/<name>
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
+
+    /// Write the unit here instead of stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
 }
 
 #[derive(Args)]
@@ -35,16 +115,16 @@ struct AuditCmd {
 
 #[derive(Subcommand)]
 enum AuditTarget {
-    /// Static ELF audit
+    /// Static binary audit (ELF, with basic PE and Mach-O support)
     Elf(ElfArgs),
 
-    /// Audit from an strace log
+    /// Audit from an strace, ltrace or perf trace log
     Trace(TraceArgs),
 }
 
 #[derive(Args)]
 struct ElfArgs {
-    /// Path to the ELF binary
+    /// Path to the binary
     #[arg(value_name = "ELF_PATH")]
     path: PathBuf,
 
@@ -59,10 +139,14 @@ struct ElfArgs {
 
 #[derive(Args)]
 struct TraceArgs {
-    /// Path to strace text log
+    /// Path to the trace text log
     #[arg(value_name = "TRACE_LOG")]
     path: PathBuf,
 
+    /// Tracer that produced the log: strace, ltrace or perf
+    #[arg(long, default_value = "strace")]
+    format: TraceFormat,
+
     /// Fail with non-zero exit if risky syscalls are detected
     #[arg(long)]
     strict: bool,
@@ -81,22 +165,34 @@ fn main() -> anyhow::Result<()> {
 
     match cli.command {
         Commands::Inspect(args) => {
-            inspect(args.path)?;
+            inspect(args.path, args.profile.as_deref(), args.policy.as_deref())?;
         }
         Commands::Audit(cmd) => match cmd.target {
             AuditTarget::Elf(args) => {
-                // thread these options into audit_elf later
-                audit_elf(args.path)?;
-                // if let Some(j) = args.json { write_report_json(j, …)?; }
-                // if let Some(m) = args.manifest { write_manifest(m, …)?; }
+                audit_elf(args.path, args.json.as_deref(), args.manifest.as_deref())?;
             }
             AuditTarget::Trace(args) => {
-                audit_trace(args.path)?;
+                audit_trace(args.path, args.format, args.manifest.as_deref())?;
                 // if args.strict { std::process::exit(if found_risks { 2 } else { 0 }); }
                 // if let Some(j) = args.json { ... }
-                // if let Some(m) = args.manifest { ... }
             }
         },
+        Commands::Init(args) => {
+            init(
+                args.from_binary.as_deref(),
+                args.output.as_deref(),
+                args.force,
+            )?;
+        }
+        Commands::Diff(args) => {
+            diff(args.old, args.new)?;
+        }
+        Commands::Report(args) => {
+            report(args.path, args.binary.as_deref(), args.output.as_deref())?;
+        }
+        Commands::SystemdUnit(args) => {
+            systemd_unit(args.path, args.exec.as_deref(), args.output.as_deref())?;
+        }
     }
 
     Ok(())
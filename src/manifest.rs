@@ -1,58 +1,449 @@
-use anyhow::{Context, Result, bail};
+use crate::host::HostPattern;
+use crate::preset;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Error, Formatter};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+// === Errors ===
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ManifestError {
+    /// Input is empty or whitespace-only.
+    Empty,
+    /// Input is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+    /// Input is not valid TOML or does not match the schema. `path` names the
+    /// offending file when the manifest was loaded from disk.
+    InvalidToml {
+        path: Option<PathBuf>,
+        source: toml::de::Error,
+    },
+    /// A required string field is empty.
+    EmptyField(&'static str),
+    /// The requested `[profile.<name>]` does not exist.
+    UnknownProfile {
+        name: String,
+        available: Vec<String>,
+    },
+    /// A field holds a value that parses but is not acceptable.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    /// A manifest file (or one it extends) could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// `extends` chains lead back to a file already being loaded.
+    ExtendsCycle(Vec<PathBuf>),
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::Empty => write!(f, "Manifest is empty"),
+            ManifestError::InvalidUtf8(_) => write!(f, "Manifest is not valid UTF-8"),
+            ManifestError::InvalidToml { path: None, .. } => write!(
+                f,
+                "Manifest TOML is invalid or does not match the expected schema"
+            ),
+            ManifestError::InvalidToml {
+                path: Some(path), ..
+            } => write!(
+                f,
+                "Manifest TOML in {} is invalid or does not match the expected schema",
+                path.display()
+            ),
+            ManifestError::EmptyField(field) => {
+                write!(f, "Manifest: '{field}' must be non-empty")
+            }
+            ManifestError::InvalidValue {
+                field,
+                value,
+                reason,
+            } => write!(f, "Manifest: invalid '{field}' entry {value:?}: {reason}"),
+            ManifestError::UnknownProfile { name, available } if available.is_empty() => {
+                write!(
+                    f,
+                    "Manifest: unknown profile '{name}' (manifest defines none)"
+                )
+            }
+            ManifestError::UnknownProfile { name, available } => write!(
+                f,
+                "Manifest: unknown profile '{name}' (available: {})",
+                available.join(", ")
+            ),
+            ManifestError::Io { path, .. } => write!(f, "failed to read {}", path.display()),
+            ManifestError::ExtendsCycle(chain) => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Manifest: extends cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::InvalidUtf8(e) => Some(e),
+            ManifestError::InvalidToml { source, .. } => Some(source),
+            ManifestError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 // === Manifest schema ===
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Manifest {
-    name: String,
-    version: String,
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// Built-in capability defaults, e.g. "web-client"; see `preset.rs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) preset: Option<String>,
+    #[serde(default)]
+    pub(crate) capabilities: Capabilities,
+    /// Runtime behaviour for long-running services.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) service: Option<Service>,
+    /// Process-level settings applied by the launcher before exec.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) runtime: Option<Runtime>,
+    /// Named secrets the payload expects to be provided at launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) secrets: Option<Secrets>,
+    /// Per-environment overrides, e.g. `[profile.prod.capabilities]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) profile: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Service {
+    #[serde(default)]
+    pub(crate) health: Option<Health>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum RestartPolicy {
+    No,
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Runtime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) restart: Option<RestartPolicy>,
+    /// Give up after this many consecutive restarts. Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_restarts: Option<u32>,
+    /// Initial delay between restarts, doubled after each attempt, e.g. "1s".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) restart_backoff: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) limits: Option<Limits>,
+    /// Linux capabilities kept across exec, without the `CAP_` prefix
+    /// (e.g. `NET_BIND_SERVICE`). Everything else is dropped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) retain_caps: Vec<String>,
+    /// What the payload sees as `/`. Defaults to the host root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rootfs: Option<RootFs>,
+    /// Realtime scheduling classes (SCHED_FIFO, SCHED_RR, SCHED_DEADLINE).
+    #[serde(default)]
+    pub(crate) allow_realtime: bool,
+    /// Setting or adjusting the system clock.
     #[serde(default)]
-    capabilities: Capabilities,
+    pub(crate) allow_clock_set: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum RootFs {
+    /// The host filesystem, narrowed by `capabilities.files`.
+    Host,
+    /// An empty read-only root with only `/usr`, `/lib` and the declared read paths
+    /// bind-mounted in.
+    Readonly,
+}
+
+/// rlimits set on the payload; unset entries inherit from the launcher.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Limits {
+    /// Open file descriptors (RLIMIT_NOFILE).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) nofile: Option<u64>,
+    /// Processes/threads for the payload's user (RLIMIT_NPROC).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) nproc: Option<u64>,
+    /// Largest file the payload may write, in bytes (RLIMIT_FSIZE).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fsize: Option<u64>,
+    /// Largest core dump, in bytes; 0 disables core dumps (RLIMIT_CORE).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) core: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Secrets {
+    /// Identifiers such as `API_TOKEN`; values never appear in the manifest.
+    pub(crate) names: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+/// Readiness probe, polled from inside the sandbox's network namespace.
+/// Exactly one of `http` or `tcp` must be set.
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
-struct Capabilities {
+pub(crate) struct Health {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) http: Option<String>,
+    /// `host:port` that must accept a TCP connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tcp: Option<String>,
+    /// How long to wait for the first healthy response, e.g. "30s". Defaults to 30s.
+    #[serde(default)]
+    pub(crate) timeout: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Profile {
+    #[serde(default)]
+    pub(crate) capabilities: Capabilities,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Capabilities {
+    #[serde(default)]
+    pub(crate) memory: Option<Memory>,
+    #[serde(default)]
+    pub(crate) files: Option<Files>,
     #[serde(default)]
-    memory: Option<Memory>,
+    pub(crate) network: Option<Network>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) sockets: Option<Sockets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) syscalls: Option<Syscalls>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) devices: Option<Devices>,
+    /// Shared IPC with the host; when unset the payload gets its own IPC namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ipc: Option<Ipc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Memory {
+    pub(crate) max_bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Files {
     #[serde(default)]
-    files: Option<Files>,
+    pub(crate) read: Option<FileRead>,
+    /// Explicit denies; these win over `read.paths`. `*` matches within one path segment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) deny_read: Vec<String>,
+    /// Private, size-limited tmpfs mounted at `/tmp` inside the sandbox.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tmp: Option<Tmp>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Tmp {
+    pub(crate) max_bytes: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FileRead {
+    pub(crate) paths: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Network {
     #[serde(default)]
-    network: Option<Network>,
+    pub(crate) connect: Option<Connect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) listen: Option<Listen>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Connect {
+    /// Names, `*.suffix` wildcards, IP literals or CIDR ranges, each with optional `:port`.
+    pub(crate) hosts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Listen {
+    /// Ports the payload may bind and accept connections on.
+    pub(crate) ports: Vec<u16>,
+    /// Local addresses to bind on; empty means any interface.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) interfaces: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
-struct Memory {
-    max_bytes: u64,
+pub(crate) struct Syscalls {
+    /// Syscall names (e.g. `openat`) the payload may make; everything else is denied.
+    pub(crate) allow: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
-struct Files {
+pub(crate) struct Ipc {
+    /// System V message queues, semaphores and shared memory segments.
     #[serde(default)]
-    read: Option<FileRead>,
+    pub(crate) sysv: bool,
+    /// POSIX shared memory under `/dev/shm`.
+    #[serde(default)]
+    pub(crate) shm: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
-struct FileRead {
-    paths: Vec<String>,
+pub(crate) struct Devices {
+    /// Device nodes under `/dev` the payload may open; all others are denied.
+    pub(crate) paths: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
-struct Network {
+pub(crate) struct Sockets {
     #[serde(default)]
-    connect: Option<Connect>,
+    pub(crate) unix: Option<UnixSockets>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(deny_unknown_fields)]
-struct Connect {
-    hosts: Vec<String>,
+pub(crate) struct UnixSockets {
+    /// Socket paths the payload may connect to; `@name` for abstract sockets.
+    pub(crate) paths: Vec<String>,
+}
+
+impl Manifest {
+    /// The manifest with `[profile.<name>]` applied. Each capability section present in
+    /// the profile (memory, files, network, sockets, syscalls, devices, ipc) replaces
+    /// the base section as a whole.
+    pub fn with_profile(&self, name: &str) -> Result<Manifest, ManifestError> {
+        let Some(profile) = self.profile.get(name) else {
+            return Err(ManifestError::UnknownProfile {
+                name: name.to_string(),
+                available: self.profile.keys().cloned().collect(),
+            });
+        };
+        let base = &self.capabilities;
+        let over = &profile.capabilities;
+        Ok(Manifest {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            preset: self.preset.clone(),
+            capabilities: Capabilities {
+                memory: over.memory.clone().or_else(|| base.memory.clone()),
+                files: over.files.clone().or_else(|| base.files.clone()),
+                network: over.network.clone().or_else(|| base.network.clone()),
+                sockets: over.sockets.clone().or_else(|| base.sockets.clone()),
+                syscalls: over.syscalls.clone().or_else(|| base.syscalls.clone()),
+                devices: over.devices.clone().or_else(|| base.devices.clone()),
+                ipc: over.ipc.or(base.ipc),
+            },
+            service: self.service.clone(),
+            runtime: self.runtime.clone(),
+            secrets: self.secrets.clone(),
+            profile: BTreeMap::new(),
+        })
+    }
+
+    /// Whether connecting to `host` (name or IP literal) on `port` is declared.
+    pub fn may_connect(&self, host: &str, port: u16) -> bool {
+        self.capabilities
+            .network
+            .as_ref()
+            .and_then(|n| n.connect.as_ref())
+            .is_some_and(|c| {
+                c.hosts
+                    .iter()
+                    .filter_map(|h| h.parse::<HostPattern>().ok())
+                    .any(|h| h.allows(host, port))
+            })
+    }
+
+    /// Whether binding `addr` (an IP literal) on `port` is declared.
+    pub fn may_listen(&self, addr: &str, port: u16) -> bool {
+        self.capabilities
+            .network
+            .as_ref()
+            .and_then(|n| n.listen.as_ref())
+            .is_some_and(|l| {
+                l.ports.contains(&port)
+                    && (l.interfaces.is_empty()
+                        || addr.parse::<IpAddr>().is_ok_and(|a| {
+                            l.interfaces
+                                .iter()
+                                .any(|i| i.parse::<IpAddr>().is_ok_and(|i| i == a))
+                        }))
+            })
+    }
+
+    /// Whether connecting to the unix socket at `path` is declared.
+    pub fn may_connect_unix(&self, path: &str) -> bool {
+        self.capabilities
+            .sockets
+            .as_ref()
+            .and_then(|s| s.unix.as_ref())
+            .is_some_and(|u| u.paths.iter().any(|p| p == path))
+    }
+
+    /// Whether `path` is readable under this manifest. Denies take precedence over allows.
+    pub fn may_read(&self, path: &str) -> bool {
+        let Some(files) = &self.capabilities.files else {
+            return false;
+        };
+        if files.deny_read.iter().any(|d| glob_covers(d, path)) {
+            return false;
+        }
+        files
+            .read
+            .as_ref()
+            .is_some_and(|r| r.paths.iter().any(|a| glob_covers(a, path)))
+    }
+}
+
+/// True if `pattern` names `path` or one of its ancestors.
+pub(crate) fn glob_covers(pattern: &str, path: &str) -> bool {
+    let pat: Vec<_> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let segs: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+    pat.len() <= segs.len() && pat.iter().zip(&segs).all(|(p, s)| segment_matches(p, s))
+}
+
+fn segment_matches(pat: &str, seg: &str) -> bool {
+    match pat.split_once('*') {
+        None => pat == seg,
+        Some((head, tail)) => {
+            let Some(rest) = seg.strip_prefix(head) else {
+                return false;
+            };
+            // try every split point for the remainder of the pattern
+            (0..=rest.len())
+                .filter(|i| rest.is_char_boundary(*i))
+                .any(|i| segment_matches(tail, &rest[i..]))
+        }
+    }
 }
 
 impl Display for Manifest {
@@ -64,30 +455,534 @@ impl Display for Manifest {
     }
 }
 
-pub fn parse_manifest(bytes: &[u8]) -> Result<Manifest> {
+pub fn parse_manifest(bytes: &[u8]) -> Result<Manifest, ManifestError> {
     // empty / whitespace-only guard (keeps a nice error)
     if bytes.is_empty() || bytes.iter().all(|b| b.is_ascii_whitespace()) {
-        bail!("Manifest is empty");
+        return Err(ManifestError::Empty);
     }
 
     // UTF-8
-    let s = std::str::from_utf8(bytes).context("Manifest is not valid UTF-8")?;
+    let s = std::str::from_utf8(bytes).map_err(ManifestError::InvalidUtf8)?;
 
     // TOML -> struct
-    let manifest: Manifest = toml::from_str(s)
-        .context("Manifest TOML is invalid or does not match the expected schema")?;
+    let manifest: Manifest =
+        toml::from_str(s).map_err(|source| ManifestError::InvalidToml { path: None, source })?;
+    validate(manifest)
+}
 
+/// Checks shared by `parse_manifest` and `load_manifest`; also applies the preset.
+fn validate(mut manifest: Manifest) -> Result<Manifest, ManifestError> {
     // basic required-field checks (adjust to your rules)
     if manifest.name.trim().is_empty() {
-        bail!("Manifest: 'name' must be non-empty");
+        return Err(ManifestError::EmptyField("name"));
     }
     if manifest.version.trim().is_empty() {
-        bail!("Manifest: 'version' must be non-empty");
+        return Err(ManifestError::EmptyField("version"));
+    }
+    for (field, v) in [("name", &manifest.name), ("version", &manifest.version)] {
+        if v.contains(char::is_control) {
+            return Err(ManifestError::InvalidValue {
+                field,
+                value: v.clone(),
+                reason: NO_CONTROL_CHARS,
+            });
+        }
+    }
+
+    if let Some(name) = &manifest.preset {
+        let Some(preset) = preset::capabilities(name) else {
+            return Err(ManifestError::InvalidValue {
+                field: "preset",
+                value: name.clone(),
+                reason: "unknown preset (expected cli-tool, web-client, daemon or build-job)",
+            });
+        };
+        let own = std::mem::take(&mut manifest.capabilities);
+        manifest.capabilities = preset::apply(name, own, preset)?;
+    }
+
+    validate_capabilities(&manifest.capabilities)?;
+    for p in manifest.profile.values() {
+        validate_capabilities(&p.capabilities)?;
+    }
+    if let Some(service) = &manifest.service {
+        validate_service(service)?;
+    }
+    if let Some(runtime) = &manifest.runtime {
+        validate_runtime(runtime)?;
+    }
+    // an explicit seccomp allow-list must not contradict allow_clock_set = false
+    let clock_set = manifest.runtime.as_ref().is_some_and(|r| r.allow_clock_set);
+    let allowed = manifest.capabilities.syscalls.iter().chain(
+        manifest
+            .profile
+            .values()
+            .filter_map(|p| p.capabilities.syscalls.as_ref()),
+    );
+    if !clock_set
+        && let Some(bad) = allowed
+            .flat_map(|s| &s.allow)
+            .find(|n| CLOCK_SET_SYSCALLS.contains(&n.as_str()))
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "capabilities.syscalls.allow",
+            value: bad.clone(),
+            reason: "sets the system clock; requires runtime.allow_clock_set = true",
+        });
+    }
+    if let Some(secrets) = &manifest.secrets {
+        validate_secrets(secrets)?;
     }
 
     Ok(manifest)
 }
 
+/// Capability names from `linux/capability.h`, without the `CAP_` prefix.
+const LINUX_CAPS: &[&str] = &[
+    "CHOWN",
+    "DAC_OVERRIDE",
+    "DAC_READ_SEARCH",
+    "FOWNER",
+    "FSETID",
+    "KILL",
+    "SETGID",
+    "SETUID",
+    "SETPCAP",
+    "LINUX_IMMUTABLE",
+    "NET_BIND_SERVICE",
+    "NET_BROADCAST",
+    "NET_ADMIN",
+    "NET_RAW",
+    "IPC_LOCK",
+    "IPC_OWNER",
+    "SYS_MODULE",
+    "SYS_RAWIO",
+    "SYS_CHROOT",
+    "SYS_PTRACE",
+    "SYS_PACCT",
+    "SYS_ADMIN",
+    "SYS_BOOT",
+    "SYS_NICE",
+    "SYS_RESOURCE",
+    "SYS_TIME",
+    "SYS_TTY_CONFIG",
+    "MKNOD",
+    "LEASE",
+    "AUDIT_WRITE",
+    "AUDIT_CONTROL",
+    "SETFCAP",
+    "MAC_OVERRIDE",
+    "MAC_ADMIN",
+    "SYSLOG",
+    "WAKE_ALARM",
+    "BLOCK_SUSPEND",
+    "AUDIT_READ",
+    "PERFMON",
+    "BPF",
+    "CHECKPOINT_RESTORE",
+];
+
+/// Syscalls that change the system clock.
+const CLOCK_SET_SYSCALLS: &[&str] = &["clock_settime", "settimeofday", "adjtimex", "clock_adjtime"];
+
+fn validate_runtime(runtime: &Runtime) -> Result<(), ManifestError> {
+    if let Some(b) = &runtime.restart_backoff
+        && parse_duration(b).is_none()
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "runtime.restart_backoff",
+            value: b.clone(),
+            reason: DURATION_HINT,
+        });
+    }
+    if runtime.restart == Some(RestartPolicy::No)
+        && let Some(n) = runtime.max_restarts
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "runtime.max_restarts",
+            value: n.to_string(),
+            reason: "has no effect with restart = \"no\"",
+        });
+    }
+    if let Some(limits) = &runtime.limits {
+        validate_limits(limits)?;
+    }
+    if let Some(cap) = runtime
+        .retain_caps
+        .iter()
+        .find(|c| !LINUX_CAPS.contains(&c.as_str()))
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "runtime.retain_caps",
+            value: cap.clone(),
+            reason: "not a Linux capability name (e.g. NET_BIND_SERVICE, without CAP_)",
+        });
+    }
+    Ok(())
+}
+
+fn validate_limits(limits: &Limits) -> Result<(), ManifestError> {
+    // a payload that cannot open a file or start its own process cannot run at all
+    for (field, v) in [
+        ("runtime.limits.nofile", limits.nofile),
+        ("runtime.limits.nproc", limits.nproc),
+    ] {
+        if v == Some(0) {
+            return Err(ManifestError::InvalidValue {
+                field,
+                value: "0".to_string(),
+                reason: "must be at least 1",
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_service(service: &Service) -> Result<(), ManifestError> {
+    if let Some(health) = &service.health {
+        validate_health(health)?;
+    }
+    Ok(())
+}
+
+const DURATION_HINT: &str = "expected a duration like \"500ms\", \"30s\", \"5m\" or \"1h\"";
+
+fn validate_health(health: &Health) -> Result<(), ManifestError> {
+    match (&health.http, &health.tcp) {
+        (Some(url), None) => {
+            let host = url
+                .strip_prefix("http://")
+                .or_else(|| url.strip_prefix("https://"))
+                .map(|rest| rest.split(['/', '?']).next().unwrap_or_default());
+            if host.is_none_or(str::is_empty) {
+                return Err(ManifestError::InvalidValue {
+                    field: "service.health.http",
+                    value: url.clone(),
+                    reason: "expected an http:// or https:// URL with a host",
+                });
+            }
+        }
+        (None, Some(addr)) => {
+            // reuse the connect-host grammar, but a probe needs one concrete endpoint
+            let concrete = matches!(
+                addr.parse::<HostPattern>(),
+                Ok(HostPattern::Name { port: Some(_), .. } | HostPattern::Ip { port: Some(_), .. })
+            );
+            if !concrete {
+                return Err(ManifestError::InvalidValue {
+                    field: "service.health.tcp",
+                    value: addr.clone(),
+                    reason: "expected host:port",
+                });
+            }
+        }
+        _ => {
+            return Err(ManifestError::InvalidValue {
+                field: "service.health",
+                value: String::new(),
+                reason: "set exactly one of http or tcp",
+            });
+        }
+    }
+    if let Some(t) = &health.timeout
+        && parse_duration(t).is_none()
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "service.health.timeout",
+            value: t.clone(),
+            reason: DURATION_HINT,
+        });
+    }
+    Ok(())
+}
+
+/// Read the manifest at `path`, following `extends = "<file>"` (relative to the
+/// extending file). Tables merge key by key, arrays append without duplicates and
+/// scalars in the extending file override the base.
+pub fn load_manifest(path: &Path) -> Result<Manifest, ManifestError> {
+    let bytes = fs::read(path).map_err(|source| ManifestError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let extends = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| toml::from_str::<toml::Table>(s).ok())
+        .is_some_and(|t| t.contains_key("extends"));
+    if !extends {
+        return parse_manifest(&bytes).map_err(|e| match e {
+            ManifestError::InvalidToml { path: None, source } => ManifestError::InvalidToml {
+                path: Some(path.to_path_buf()),
+                source,
+            },
+            e => e,
+        });
+    }
+    let table = load_table(path, &mut Vec::new())?;
+    let manifest =
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|source| ManifestError::InvalidToml {
+                path: Some(path.to_path_buf()),
+                source,
+            })?;
+    validate(manifest)
+}
+
+fn load_table(path: &Path, stack: &mut Vec<PathBuf>) -> Result<toml::Table, ManifestError> {
+    let io_err = |source| ManifestError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let canon = fs::canonicalize(path).map_err(io_err)?;
+    if stack.contains(&canon) {
+        stack.push(canon);
+        return Err(ManifestError::ExtendsCycle(std::mem::take(stack)));
+    }
+    let bytes = fs::read(&canon).map_err(io_err)?;
+    let s = std::str::from_utf8(&bytes).map_err(ManifestError::InvalidUtf8)?;
+    let mut table: toml::Table =
+        toml::from_str(s).map_err(|source| ManifestError::InvalidToml {
+            path: Some(path.to_path_buf()),
+            source,
+        })?;
+
+    if let Some(ext) = table.remove("extends") {
+        let Some(rel) = ext.as_str() else {
+            return Err(ManifestError::InvalidValue {
+                field: "extends",
+                value: ext.to_string(),
+                reason: "expected a path to another manifest",
+            });
+        };
+        let base_path = canon.parent().unwrap_or(Path::new("/")).join(rel);
+        stack.push(canon);
+        let base = load_table(&base_path, stack)?;
+        stack.pop();
+        table = merge_tables(base, table);
+    }
+    Ok(table)
+}
+
+pub(crate) fn merge_tables(mut base: toml::Table, over: toml::Table) -> toml::Table {
+    use toml::Value;
+    for (key, value) in over {
+        let merged = match (base.remove(&key), value) {
+            (Some(Value::Table(b)), Value::Table(o)) => Value::Table(merge_tables(b, o)),
+            (Some(Value::Array(mut b)), Value::Array(o)) => {
+                for v in o {
+                    if !b.contains(&v) {
+                        b.push(v);
+                    }
+                }
+                Value::Array(b)
+            }
+            (_, v) => v,
+        };
+        base.insert(key, merged);
+    }
+    base
+}
+
+/// Parse `<integer><unit>` with unit one of ms, s, m, h.
+pub(crate) fn parse_duration(s: &str) -> Option<std::time::Duration> {
+    use std::time::Duration;
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(n)),
+        "s" => Some(Duration::from_secs(n)),
+        "m" => n.checked_mul(60).map(Duration::from_secs),
+        "h" => n.checked_mul(3600).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+fn validate_secrets(secrets: &Secrets) -> Result<(), ManifestError> {
+    let mut seen = std::collections::BTreeSet::new();
+    for name in &secrets.names {
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let reason = if !valid {
+            "secret names must be identifiers ([A-Za-z_][A-Za-z0-9_]*)"
+        } else if !seen.insert(name) {
+            "duplicate secret name"
+        } else {
+            continue;
+        };
+        return Err(ManifestError::InvalidValue {
+            field: "secrets.names",
+            value: name.clone(),
+            reason,
+        });
+    }
+    Ok(())
+}
+
+const DOT_SEGMENTS: &str = "paths must not contain '.' or '..' segments";
+
+fn has_dot_segment(path: &str) -> bool {
+    path.split('/').any(|s| s == "." || s == "..")
+}
+
+/// Whether `path` is acceptable as a `files.read` or `deny_read` entry.
+pub(crate) fn is_manifest_path(path: &str) -> bool {
+    path.starts_with('/') && !has_dot_segment(path) && !path.contains(char::is_control)
+}
+
+/// Manifest text ends up in generated unit files and policies, one setting per line.
+const NO_CONTROL_CHARS: &str = "must not contain control characters";
+
+fn validate_capabilities(caps: &Capabilities) -> Result<(), ManifestError> {
+    let files = caps.files.as_ref();
+    let path_lists = [
+        (
+            "capabilities.files.read.paths",
+            files.and_then(|f| f.read.as_ref()).map(|r| &r.paths),
+        ),
+        ("capabilities.files.deny_read", files.map(|f| &f.deny_read)),
+        (
+            "capabilities.sockets.unix.paths",
+            caps.sockets
+                .as_ref()
+                .and_then(|s| s.unix.as_ref())
+                .map(|u| &u.paths),
+        ),
+        (
+            "capabilities.devices.paths",
+            caps.devices.as_ref().map(|d| &d.paths),
+        ),
+    ];
+    for (field, paths) in path_lists {
+        if let Some(bad) = paths
+            .into_iter()
+            .flatten()
+            .find(|p| p.contains(char::is_control))
+        {
+            return Err(ManifestError::InvalidValue {
+                field,
+                value: bad.clone(),
+                reason: NO_CONTROL_CHARS,
+            });
+        }
+    }
+    // matching is purely lexical, so paths must be absolute and free of `.`/`..`
+    // segments or `/etc/../home/x` would slip past a deny on `/home/*`
+    if let Some(files) = &caps.files {
+        let reads = files.read.iter().flat_map(|r| &r.paths);
+        let lists = [
+            (
+                "capabilities.files.read.paths",
+                "read paths must be absolute",
+                reads.collect::<Vec<_>>(),
+            ),
+            (
+                "capabilities.files.deny_read",
+                "deny paths must be absolute",
+                files.deny_read.iter().collect(),
+            ),
+        ];
+        for (field, relative, paths) in lists {
+            for p in paths {
+                let reason = if !p.starts_with('/') {
+                    relative
+                } else if has_dot_segment(p) {
+                    DOT_SEGMENTS
+                } else {
+                    continue;
+                };
+                return Err(ManifestError::InvalidValue {
+                    field,
+                    value: p.clone(),
+                    reason,
+                });
+            }
+        }
+        if let Some(tmp) = &files.tmp
+            && tmp.max_bytes == 0
+        {
+            return Err(ManifestError::InvalidValue {
+                field: "capabilities.files.tmp.max_bytes",
+                value: "0".to_string(),
+                reason: "a scratch tmpfs needs a non-zero size",
+            });
+        }
+    }
+    if let Some(connect) = caps.network.as_ref().and_then(|n| n.connect.as_ref()) {
+        for h in &connect.hosts {
+            if let Err(reason) = h.parse::<HostPattern>() {
+                return Err(ManifestError::InvalidValue {
+                    field: "capabilities.network.connect.hosts",
+                    value: h.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+    if let Some(listen) = caps.network.as_ref().and_then(|n| n.listen.as_ref()) {
+        if let Some(p) = listen.ports.iter().find(|p| **p == 0) {
+            return Err(ManifestError::InvalidValue {
+                field: "capabilities.network.listen.ports",
+                value: p.to_string(),
+                reason: "port must be 1-65535",
+            });
+        }
+        if let Some(i) = listen
+            .interfaces
+            .iter()
+            .find(|i| i.parse::<IpAddr>().is_err())
+        {
+            return Err(ManifestError::InvalidValue {
+                field: "capabilities.network.listen.interfaces",
+                value: i.clone(),
+                reason: "interfaces must be IP addresses",
+            });
+        }
+    }
+    if let Some(syscalls) = &caps.syscalls
+        && let Some(bad) = syscalls.allow.iter().find(|n| {
+            n.is_empty()
+                || !n
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        })
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "capabilities.syscalls.allow",
+            value: bad.clone(),
+            reason: "syscall names are lowercase identifiers such as \"openat\"",
+        });
+    }
+    if let Some(unix) = caps.sockets.as_ref().and_then(|s| s.unix.as_ref()) {
+        for p in &unix.paths {
+            if !p.starts_with('/') && !p.starts_with('@') {
+                return Err(ManifestError::InvalidValue {
+                    field: "capabilities.sockets.unix.paths",
+                    value: p.clone(),
+                    reason: "socket paths must be absolute or @abstract",
+                });
+            }
+        }
+    }
+    if let Some(devices) = &caps.devices
+        && let Some(bad) = devices
+            .paths
+            .iter()
+            .find(|p| !p.starts_with("/dev/") || has_dot_segment(p))
+    {
+        return Err(ManifestError::InvalidValue {
+            field: "capabilities.devices.paths",
+            value: bad.clone(),
+            reason: "device paths must be nodes under /dev/",
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod prop {
     use super::*;
@@ -108,8 +1003,10 @@ mod prop {
 
     fn s_path() -> impl Strategy<Value = String> {
         // a few path segments like "/etc/conf", "/a/b", etc.
-        vec(string_regex("[a-zA-Z0-9._-]{1,8}").unwrap(), 1..5)
-            .prop_map(|segs| format!("/{}", segs.join("/")))
+        let seg = string_regex("[a-zA-Z0-9._-]{1,8}")
+            .unwrap()
+            .prop_filter("no dot segments", |s| s != "." && s != "..");
+        vec(seg, 1..5).prop_map(|segs| format!("/{}", segs.join("/")))
     }
 
     fn s_host() -> impl Strategy<Value = String> {
@@ -125,17 +1022,30 @@ mod prop {
     fn s_capabilities() -> impl Strategy<Value = Capabilities> {
         let mem = option::of((1u64..=16_000_000u64).prop_map(|max| Memory { max_bytes: max }));
         let files = option::of(
-            option::of(vec(s_path(), 1..5).prop_map(|paths| FileRead { paths }))
-                .prop_map(|read| Files { read }),
+            option::of(vec(s_path(), 1..5).prop_map(|paths| FileRead { paths })).prop_map(|read| {
+                Files {
+                    read,
+                    deny_read: Vec::new(),
+                    tmp: None,
+                }
+            }),
         );
         let net = option::of(
-            option::of(vec(s_host(), 1..5).prop_map(|hosts| Connect { hosts }))
-                .prop_map(|connect| Network { connect }),
+            option::of(vec(s_host(), 1..5).prop_map(|hosts| Connect { hosts })).prop_map(
+                |connect| Network {
+                    connect,
+                    listen: None,
+                },
+            ),
         );
         (mem, files, net).prop_map(|(memory, files, network)| Capabilities {
             memory,
             files,
             network,
+            sockets: None,
+            syscalls: None,
+            devices: None,
+            ipc: None,
         })
     }
 
@@ -144,7 +1054,12 @@ mod prop {
             Manifest {
                 name,
                 version,
+                preset: None,
                 capabilities,
+                service: None,
+                runtime: None,
+                secrets: None,
+                profile: BTreeMap::new(),
             }
         })
     }
@@ -237,6 +1152,7 @@ name = ""
 version = "0.1.0"
 "#;
         let err = parse_manifest(bad).unwrap_err();
+        assert!(matches!(err, ManifestError::EmptyField("name")));
         assert!(format!("{err:#}").contains("'name' must be non-empty"));
 
         // Empty version
@@ -247,4 +1163,493 @@ version = ""
         let err = parse_manifest(bad).unwrap_err();
         assert!(format!("{err:#}").contains("'version' must be non-empty"));
     }
+
+    #[test]
+    fn deny_read_wins_over_broad_allow() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.files]
+deny_read = ["/home/*/.ssh", "/home/*/*.key"]
+
+[capabilities.files.read]
+paths = ["/home"]
+"#,
+        )
+        .expect("valid manifest");
+
+        assert!(m.may_read("/home/alice/notes.txt"));
+        assert!(!m.may_read("/home/alice/.ssh"));
+        assert!(!m.may_read("/home/alice/.ssh/id_ed25519"));
+        assert!(!m.may_read("/home/bob/server.key"));
+        assert!(!m.may_read("/etc/passwd"));
+    }
+
+    #[test]
+    fn deny_read_must_be_absolute() {
+        let bad = br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.files]
+deny_read = [".ssh"]
+"#;
+        let err = parse_manifest(bad).unwrap_err();
+        assert!(matches!(err, ManifestError::InvalidValue { .. }));
+        assert!(format!("{err:#}").contains("deny paths must be absolute"));
+    }
+
+    #[test]
+    fn dot_segments_cannot_bypass_denies() {
+        for read in ["/etc/../home/alice/.ssh", "/etc/./passwd", "etc"] {
+            let src = format!(
+                "name = \"a\"\nversion = \"0.1.0\"\n[capabilities.files]\n\
+                 deny_read = [\"/home/*/.ssh\"]\n[capabilities.files.read]\npaths = [\"{read}\"]\n"
+            );
+            assert!(
+                matches!(
+                    parse_manifest(src.as_bytes()),
+                    Err(ManifestError::InvalidValue {
+                        field: "capabilities.files.read.paths",
+                        ..
+                    })
+                ),
+                "{read}"
+            );
+        }
+        let bad = b"name = \"a\"\nversion = \"0.1.0\"\n[capabilities.files]\ndeny_read = [\"/home/../root\"]\n";
+        assert!(parse_manifest(bad).is_err());
+    }
+
+    #[test]
+    fn profile_overrides_replace_whole_sections() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.memory]
+max_bytes = 1024
+
+[capabilities.network.connect]
+hosts = ["staging.example.com:443"]
+
+[profile.prod.capabilities.network.connect]
+hosts = ["api.example.com:443"]
+"#,
+        )
+        .expect("valid manifest");
+
+        let prod = m.with_profile("prod").expect("prod profile");
+        assert_eq!(
+            prod.capabilities.memory.as_ref().map(|x| x.max_bytes),
+            Some(1024)
+        );
+        assert_eq!(
+            prod.capabilities.network.unwrap().connect.unwrap().hosts,
+            ["api.example.com:443"]
+        );
+        assert!(prod.profile.is_empty());
+
+        let err = m.with_profile("dev").unwrap_err();
+        assert!(format!("{err}").contains("available: prod"));
+    }
+
+    #[test]
+    fn service_health_is_validated() {
+        let ok = br#"
+name = "svc"
+version = "0.1.0"
+
+[service.health]
+http = "http://127.0.0.1:8080/healthz"
+timeout = "30s"
+"#;
+        parse_manifest(ok).expect("valid health check");
+
+        let bad_url = br#"
+name = "svc"
+version = "0.1.0"
+
+[service.health]
+http = "127.0.0.1:8080/healthz"
+"#;
+        let err = parse_manifest(bad_url).unwrap_err();
+        assert!(format!("{err}").contains("service.health.http"));
+
+        let bad_timeout = br#"
+name = "svc"
+version = "0.1.0"
+
+[service.health]
+http = "http://127.0.0.1:8080/healthz"
+timeout = "soon"
+"#;
+        let err = parse_manifest(bad_timeout).unwrap_err();
+        assert!(format!("{err}").contains("service.health.timeout"));
+
+        let tcp = br#"
+name = "svc"
+version = "0.1.0"
+
+[service.health]
+tcp = "127.0.0.1:5432"
+"#;
+        parse_manifest(tcp).expect("valid tcp probe");
+
+        let both = br#"
+name = "svc"
+version = "0.1.0"
+
+[service.health]
+http = "http://127.0.0.1:8080/healthz"
+tcp = "127.0.0.1:8080"
+"#;
+        let err = parse_manifest(both).unwrap_err();
+        assert!(format!("{err}").contains("exactly one of http or tcp"));
+    }
+
+    #[test]
+    fn restart_policy_is_validated() {
+        let ok = br#"
+name = "svc"
+version = "0.1.0"
+
+[runtime]
+restart = "on-failure"
+max_restarts = 5
+restart_backoff = "1s"
+"#;
+        let m = parse_manifest(ok).expect("valid restart policy");
+        assert_eq!(
+            m.runtime.and_then(|r| r.restart),
+            Some(RestartPolicy::OnFailure)
+        );
+
+        for body in [
+            "restart = \"sometimes\"",
+            "restart = \"always\"\nrestart_backoff = \"fast\"",
+            "restart = \"no\"\nmax_restarts = 3",
+        ] {
+            let src = format!("name = \"svc\"\nversion = \"0.1.0\"\n[runtime]\n{body}\n");
+            assert!(parse_manifest(src.as_bytes()).is_err(), "{body}");
+        }
+        let old = b"name = \"svc\"\nversion = \"0.1.0\"\n[service]\nrestart = \"always\"\n";
+        assert!(parse_manifest(old).is_err());
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        use std::time::Duration;
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+
+    #[test]
+    fn listen_ports_and_interfaces_are_validated_and_matched() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.network.listen]
+ports = [8080]
+interfaces = ["127.0.0.1"]
+"#,
+        )
+        .expect("valid manifest");
+        assert!(m.may_listen("127.0.0.1", 8080));
+        assert!(!m.may_listen("0.0.0.0", 8080));
+        assert!(!m.may_listen("127.0.0.1", 9090));
+
+        for bad in ["ports = [0]", "ports = [80]\ninterfaces = [\"eth0\"]"] {
+            let src = format!(
+                "name = \"demo\"\nversion = \"0.1.0\"\n[capabilities.network.listen]\n{bad}\n"
+            );
+            assert!(matches!(
+                parse_manifest(src.as_bytes()),
+                Err(ManifestError::InvalidValue { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn unix_socket_paths_are_validated_and_matched() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.sockets.unix]
+paths = ["/run/postgresql/.s.PGSQL.5432", "@/tmp/.X11-unix/X0"]
+"#,
+        )
+        .expect("valid manifest");
+        assert!(m.may_connect_unix("/run/postgresql/.s.PGSQL.5432"));
+        assert!(m.may_connect_unix("@/tmp/.X11-unix/X0"));
+        assert!(!m.may_connect_unix("/var/run/docker.sock"));
+
+        let bad = br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.sockets.unix]
+paths = ["docker.sock"]
+"#;
+        assert!(matches!(
+            parse_manifest(bad),
+            Err(ManifestError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn secret_names_must_be_unique_identifiers() {
+        let ok = br#"
+name = "demo"
+version = "0.1.0"
+
+[secrets]
+names = ["API_TOKEN", "_db_password"]
+"#;
+        assert!(parse_manifest(ok).is_ok());
+
+        for names in [r#"["API-TOKEN"]"#, r#"["1TOKEN"]"#, r#"["A", "A"]"#] {
+            let src = format!("name = \"demo\"\nversion = \"0.1.0\"\n[secrets]\nnames = {names}\n");
+            assert!(matches!(
+                parse_manifest(src.as_bytes()),
+                Err(ManifestError::InvalidValue {
+                    field: "secrets.names",
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn preset_fills_only_unset_sections() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+preset = "web-client"
+
+[capabilities.memory]
+max_bytes = 1048576
+"#,
+        )
+        .expect("valid manifest");
+        assert_eq!(
+            m.capabilities.memory.as_ref().map(|x| x.max_bytes),
+            Some(1048576)
+        );
+        assert!(m.may_read("/etc/resolv.conf"));
+
+        let bad = b"name = \"demo\"\nversion = \"0.1.0\"\npreset = \"kitchen-sink\"\n";
+        assert!(matches!(
+            parse_manifest(bad),
+            Err(ManifestError::InvalidValue {
+                field: "preset",
+                ..
+            })
+        ));
+        for name in ["cli-tool", "web-client", "daemon", "build-job"] {
+            assert!(preset::capabilities(name).is_some());
+        }
+    }
+
+    #[test]
+    fn presets_merge_like_extends() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+preset = "build-job"
+
+[capabilities.files]
+deny_read = ["/etc/passwd"]
+
+[capabilities.files.tmp]
+max_bytes = 1048576
+"#,
+        )
+        .expect("valid manifest");
+        // adding a deny or a tmp dir keeps the preset's reads and denies
+        assert!(m.may_read("/usr/bin/cc"));
+        assert!(m.may_read("/etc/hosts"));
+        assert!(!m.may_read("/etc/passwd"));
+        assert!(!m.may_read("/etc/shadow"));
+        assert!(!m.may_read("/etc/ssh/ssh_host_ed25519_key"));
+        assert_eq!(
+            m.capabilities
+                .files
+                .as_ref()
+                .and_then(|f| f.tmp.as_ref())
+                .map(|t| t.max_bytes),
+            Some(1048576)
+        );
+    }
+
+    #[test]
+    fn runtime_limits_parse_and_reject_zero_fds() {
+        let m = parse_manifest(
+            b"name = \"a\"\nversion = \"0.1.0\"\n[runtime.limits]\nnofile = 256\ncore = 0\n",
+        )
+        .unwrap();
+        let limits = m.runtime.and_then(|r| r.limits).unwrap();
+        assert_eq!(
+            (limits.nofile, limits.core, limits.nproc),
+            (Some(256), Some(0), None)
+        );
+
+        let err =
+            parse_manifest(b"name = \"a\"\nversion = \"0.1.0\"\n[runtime.limits]\nnofile = 0\n")
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            ManifestError::InvalidValue {
+                field: "runtime.limits.nofile",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        let injected = "name = \"svc\\nExecStartPre=+/bin/sh\"\nversion = \"0.1.0\"\n";
+        assert!(matches!(
+            parse_manifest(injected.as_bytes()),
+            Err(ManifestError::InvalidValue { field: "name", .. })
+        ));
+        let injected = "name = \"a\"\nversion = \"0.1.0\"\n[capabilities.files.read]\n\
+                        paths = [\"/etc/x\\nExecStopPost=+/tmp/evil\"]\n";
+        assert!(matches!(
+            parse_manifest(injected.as_bytes()),
+            Err(ManifestError::InvalidValue {
+                field: "capabilities.files.read.paths",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn device_paths_must_stay_under_dev() {
+        let base = "name = \"a\"\nversion = \"0.1.0\"\n[capabilities.devices]\n";
+        let ok = format!("{base}paths = [\"/dev/null\", \"/dev/dri/renderD128\"]\n");
+        assert!(parse_manifest(ok.as_bytes()).is_ok());
+        for bad in ["/etc/passwd", "dev/null", "/dev/../etc/shadow"] {
+            let src = format!("{base}paths = [\"{bad}\"]\n");
+            assert!(parse_manifest(src.as_bytes()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn clock_syscalls_need_allow_clock_set() {
+        let src = "name = \"a\"\nversion = \"0.1.0\"\n\
+                   [capabilities.syscalls]\nallow = [\"read\", \"clock_settime\"]\n";
+        assert!(matches!(
+            parse_manifest(src.as_bytes()),
+            Err(ManifestError::InvalidValue {
+                field: "capabilities.syscalls.allow",
+                ..
+            })
+        ));
+        let ok = format!("{src}[runtime]\nallow_clock_set = true\n");
+        let m = parse_manifest(ok.as_bytes()).unwrap();
+        assert!(
+            m.runtime
+                .is_some_and(|r| r.allow_clock_set && !r.allow_realtime)
+        );
+    }
+
+    #[test]
+    fn retain_caps_must_be_linux_capabilities() {
+        let base = "name = \"a\"\nversion = \"0.1.0\"\n[runtime]\n";
+        let ok = format!("{base}retain_caps = [\"NET_BIND_SERVICE\"]\n");
+        assert!(parse_manifest(ok.as_bytes()).is_ok());
+        for bad in ["CAP_NET_BIND_SERVICE", "net_bind_service", "ROOT"] {
+            let src = format!("{base}retain_caps = [\"{bad}\"]\n");
+            assert!(
+                matches!(
+                    parse_manifest(src.as_bytes()),
+                    Err(ManifestError::InvalidValue {
+                        field: "runtime.retain_caps",
+                        ..
+                    })
+                ),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn extends_merges_base_and_detects_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, body: &str| fs::write(dir.path().join(name), body).unwrap();
+        write(
+            "base.toml",
+            "name = \"base\"\nversion = \"1.0.0\"\n[capabilities.memory]\nmax_bytes = 1024\n\
+             [capabilities.files.read]\npaths = [\"/etc/ssl/certs\"]\n",
+        );
+        write(
+            "app.toml",
+            "extends = \"base.toml\"\nname = \"app\"\n[capabilities.files.read]\npaths = [\"/etc/app\"]\n",
+        );
+        let m = load_manifest(&dir.path().join("app.toml")).expect("merged manifest");
+        assert_eq!(m.name, "app");
+        assert_eq!(m.version, "1.0.0");
+        assert_eq!(
+            m.capabilities.memory.as_ref().map(|x| x.max_bytes),
+            Some(1024)
+        );
+        assert!(m.may_read("/etc/ssl/certs/ca.pem") && m.may_read("/etc/app"));
+
+        write("a.toml", "extends = \"b.toml\"\nname = \"a\"\n");
+        write("b.toml", "extends = \"a.toml\"\nversion = \"0.1.0\"\n");
+        let err = load_manifest(&dir.path().join("a.toml")).unwrap_err();
+        assert!(matches!(&err, ManifestError::ExtendsCycle(chain) if chain.len() == 3));
+        assert!(format!("{err}").contains("extends cycle"));
+
+        // a broken base file is named in the error
+        write("broken.toml", "name = [\n");
+        write("child.toml", "extends = \"broken.toml\"\nname = \"c\"\n");
+        let err = load_manifest(&dir.path().join("child.toml")).unwrap_err();
+        assert!(matches!(
+            &err,
+            ManifestError::InvalidToml { path: Some(p), .. } if p.ends_with("broken.toml")
+        ));
+        assert!(format!("{err}").contains("broken.toml"));
+    }
+
+    #[test]
+    fn connect_hosts_are_validated_and_matched() {
+        let m = parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.network.connect]
+hosts = ["*.example.com:443", "10.0.0.0/8"]
+"#,
+        )
+        .expect("valid manifest");
+        assert!(m.may_connect("api.example.com", 443));
+        assert!(m.may_connect("10.20.30.40", 5432));
+        assert!(!m.may_connect("example.org", 443));
+
+        let bad = br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.network.connect]
+hosts = ["api.*.com:443"]
+"#;
+        let err = parse_manifest(bad).unwrap_err();
+        assert!(format!("{err}").contains("leftmost label"));
+    }
 }
@@ -0,0 +1,140 @@
+//! Organisation-wide admission policy (conventionally `/etc/zerok/policy.toml`) bounding
+//! what any manifest may request on this host.
+
+use crate::manifest::{Manifest, glob_covers};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Policy {
+    /// Upper bound for `capabilities.memory.max_bytes`; manifests must set one.
+    pub max_memory_bytes: Option<u64>,
+    /// Paths no manifest may be granted read access to (globs allowed).
+    #[serde(default)]
+    pub banned_paths: Vec<String>,
+}
+
+pub fn load_policy(path: &Path) -> Result<Policy> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str(&text).with_context(|| format!("invalid policy {}", path.display()))
+}
+
+impl Policy {
+    /// Every way `m` exceeds this policy, including through any `[profile.*]`;
+    /// empty if it is admissible.
+    pub fn violations(&self, m: &Manifest) -> Vec<String> {
+        let mut out = self.check(m);
+        for name in m.profile.keys() {
+            if let Ok(merged) = m.with_profile(name) {
+                out.extend(
+                    self.check(&merged)
+                        .into_iter()
+                        .map(|v| format!("profile {name}: {v}")),
+                );
+            }
+        }
+        out
+    }
+
+    fn check(&self, m: &Manifest) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(max) = self.max_memory_bytes {
+            match &m.capabilities.memory {
+                Some(mem) if mem.max_bytes > max => out.push(format!(
+                    "memory.max_bytes {} exceeds policy limit {max}",
+                    mem.max_bytes
+                )),
+                Some(_) => {}
+                None => out.push(format!("no memory limit set (policy limit {max})")),
+            }
+        }
+        let reads = m
+            .capabilities
+            .files
+            .as_ref()
+            .and_then(|f| f.read.as_ref())
+            .map(|r| r.paths.as_slice())
+            .unwrap_or(&[]);
+        // read paths carry no `.`/`..` segments (see validate_capabilities), so a
+        // lexical match in either direction is enough
+        for banned in &self.banned_paths {
+            // either a broad grant that reaches the banned path, or a grant inside it
+            if m.may_read(banned) || reads.iter().any(|p| glob_covers(banned, p)) {
+                out.push(format!("files.read reaches banned path {banned}"));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::parse_manifest;
+
+    #[test]
+    fn flags_memory_and_banned_paths() {
+        let policy: Policy = toml::from_str(
+            "max_memory_bytes = 1024\nbanned_paths = [\"/etc/shadow\", \"/root\"]\n",
+        )
+        .unwrap();
+        let m = parse_manifest(
+            br#"
+name = "a"
+version = "0.1.0"
+[capabilities.memory]
+max_bytes = 4096
+[capabilities.files.read]
+paths = ["/etc", "/root/.config"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.violations(&m),
+            [
+                "memory.max_bytes 4096 exceeds policy limit 1024",
+                "files.read reaches banned path /etc/shadow",
+                "files.read reaches banned path /root",
+            ]
+        );
+
+        let ok = parse_manifest(
+            br#"
+name = "a"
+version = "0.1.0"
+[capabilities.memory]
+max_bytes = 512
+[capabilities.files]
+deny_read = ["/etc/shadow"]
+[capabilities.files.read]
+paths = ["/etc"]
+"#,
+        )
+        .unwrap();
+        assert!(policy.violations(&ok).is_empty());
+
+        let via_profile = parse_manifest(
+            br#"
+name = "a"
+version = "0.1.0"
+[capabilities.memory]
+max_bytes = 512
+[profile.dev.capabilities.files.read]
+paths = ["/etc/shadow"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.violations(&via_profile),
+            ["profile dev: files.read reaches banned path /etc/shadow"]
+        );
+
+        // `..` cannot route around a ban: such manifests never parse
+        let dotted = b"name = \"a\"\nversion = \"0.1.0\"\n[capabilities.files.read]\npaths = [\"/usr/../etc/shadow\"]\n";
+        assert!(parse_manifest(dotted).is_err());
+    }
+}
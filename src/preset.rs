@@ -0,0 +1,77 @@
+//! Built-in capability presets selected with `preset = "<name>"` in a manifest.
+//! The manifest's own `[capabilities]` are merged over the preset the same way
+//! `extends` merges over a base file: tables merge key by key, lists are unioned
+//! and the manifest's scalars win.
+
+use crate::manifest::{Capabilities, FileRead, Files, ManifestError, Memory, merge_tables};
+
+/// Name-service and TLS files needed to resolve hosts and verify certificates.
+const NET_CLIENT_READS: &[&str] = &[
+    "/etc/hosts",
+    "/etc/nsswitch.conf",
+    "/etc/resolv.conf",
+    "/etc/ssl/certs",
+];
+
+/// Credentials and host keys under `/etc` that a build never needs.
+const ETC_SECRETS: &[&str] = &[
+    "/etc/shadow",
+    "/etc/gshadow",
+    "/etc/sudoers",
+    "/etc/sudoers.d",
+    "/etc/ssh",
+    "/etc/ssl/private",
+];
+
+fn caps(max_bytes: u64, reads: &[&str], denies: &[&str]) -> Capabilities {
+    Capabilities {
+        memory: Some(Memory { max_bytes }),
+        files: (!reads.is_empty()).then(|| Files {
+            read: Some(FileRead {
+                paths: reads.iter().map(|s| s.to_string()).collect(),
+            }),
+            deny_read: denies.iter().map(|s| s.to_string()).collect(),
+            tmp: None,
+        }),
+        ..Capabilities::default()
+    }
+}
+
+pub(crate) fn capabilities(name: &str) -> Option<Capabilities> {
+    const MIB: u64 = 1 << 20;
+    match name {
+        "cli-tool" => Some(caps(256 * MIB, &[], &[])),
+        "web-client" => Some(caps(512 * MIB, NET_CLIENT_READS, &[])),
+        "daemon" => Some(caps(
+            512 * MIB,
+            &[
+                "/etc/hosts",
+                "/etc/localtime",
+                "/etc/nsswitch.conf",
+                "/etc/resolv.conf",
+            ],
+            &[],
+        )),
+        "build-job" => Some(caps(4096 * MIB, &["/usr", "/etc"], ETC_SECRETS)),
+        _ => None,
+    }
+}
+
+/// Merge `caps` over `preset` (see the module docs).
+pub(crate) fn apply(
+    name: &str,
+    caps: Capabilities,
+    preset: Capabilities,
+) -> Result<Capabilities, ManifestError> {
+    let table = |c: Capabilities| {
+        toml::Table::try_from(c).map_err(|_| ManifestError::InvalidValue {
+            field: "preset",
+            value: name.to_string(),
+            reason: "capabilities could not be merged with the preset",
+        })
+    };
+    let merged = merge_tables(table(preset)?, table(caps)?);
+    toml::Value::Table(merged)
+        .try_into()
+        .map_err(|source| ManifestError::InvalidToml { path: None, source })
+}
@@ -0,0 +1,470 @@
+use crate::audit::{Hardening, elf_hardening};
+use crate::json::Json;
+use crate::manifest::{self, Manifest, RootFs};
+use anyhow::{Context, Result};
+use std::{fmt::Write, fs, path::Path};
+
+/// Kernel features the capability model relies on, as seen on this host.
+struct HostSupport {
+    landlock: bool,
+    cgroup_v2: bool,
+}
+
+impl HostSupport {
+    fn probe() -> Self {
+        let lsm = fs::read_to_string("/sys/kernel/security/lsm").unwrap_or_default();
+        HostSupport {
+            landlock: lsm.split(',').any(|m| m.trim() == "landlock"),
+            cgroup_v2: Path::new("/sys/fs/cgroup/cgroup.controllers").exists(),
+        }
+    }
+}
+
+/// Write a reviewer-facing report for `manifest_path` (and optionally its binary).
+/// The format follows the output extension: `.json` for JSON, anything else Markdown.
+/// Without an output path the Markdown report goes to stdout.
+pub fn report<P: AsRef<Path>>(
+    manifest_path: P,
+    binary: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let path = manifest_path.as_ref();
+    let manifest = manifest::load_manifest(path)?;
+    let hardening = binary.map(elf_hardening).transpose()?;
+    let host = HostSupport::probe();
+    let gaps = known_gaps(&manifest, hardening.as_ref(), &host);
+
+    let is_json = output.is_some_and(|o| o.extension().is_some_and(|e| e == "json"));
+    let text = if is_json {
+        format!("{}\n", to_json(&manifest, hardening.as_ref(), &host, &gaps))
+    } else {
+        to_markdown(&manifest, hardening.as_ref(), &host, &gaps)
+    };
+
+    match output {
+        Some(o) => {
+            fs::write(o, text).with_context(|| format!("failed to write {}", o.display()))?;
+            println!("Report written to {}", o.display());
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+fn known_gaps(m: &Manifest, h: Option<&Hardening>, host: &HostSupport) -> Vec<String> {
+    let mut gaps = Vec::new();
+    if m.capabilities.memory.is_none() {
+        gaps.push("No memory limit declared; the payload's memory is unbounded.".to_string());
+    }
+    if !host.landlock {
+        gaps.push("Landlock is not enabled on this host; file capabilities cannot be enforced by the kernel.".to_string());
+    }
+    if !host.cgroup_v2 {
+        gaps.push(
+            "cgroup v2 is not mounted on this host; the memory limit cannot be enforced."
+                .to_string(),
+        );
+    }
+    if hosts(m).iter().any(|h| h.contains('*')) {
+        gaps.push(
+            "Wildcard hosts are resolved at enforcement time and cannot be pinned ahead of launch."
+                .to_string(),
+        );
+    }
+    match h {
+        None => gaps.push("No binary given; hardening was not assessed.".to_string()),
+        Some(h) if h.score() < 70 => gaps.push(format!(
+            "Binary hardening score is low ({}/100).",
+            h.score()
+        )),
+        Some(_) => {}
+    }
+    gaps.push(
+        "Signature and provenance are not checked: this build has no package signing support."
+            .to_string(),
+    );
+    gaps
+}
+
+fn reads(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .files
+        .as_ref()
+        .and_then(|f| f.read.as_ref())
+        .map(|r| r.paths.clone())
+        .unwrap_or_default()
+}
+
+fn denies(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .files
+        .as_ref()
+        .map(|f| f.deny_read.clone())
+        .unwrap_or_default()
+}
+
+fn tmp_max_bytes(m: &Manifest) -> Option<u64> {
+    m.capabilities
+        .files
+        .as_ref()
+        .and_then(|f| f.tmp.as_ref())
+        .map(|t| t.max_bytes)
+}
+
+fn hosts(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .network
+        .as_ref()
+        .and_then(|n| n.connect.as_ref())
+        .map(|c| c.hosts.clone())
+        .unwrap_or_default()
+}
+
+fn secret_names(m: &Manifest) -> Vec<String> {
+    m.secrets
+        .as_ref()
+        .map(|s| s.names.clone())
+        .unwrap_or_default()
+}
+
+/// `[runtime]` settings as `key=value` items.
+fn runtime_settings(m: &Manifest) -> Vec<String> {
+    let mut out = Vec::new();
+    let runtime = m.runtime.as_ref();
+    if let Some(l) = runtime.and_then(|r| r.limits.as_ref()) {
+        for (k, v) in [
+            ("nofile", l.nofile),
+            ("nproc", l.nproc),
+            ("fsize", l.fsize),
+            ("core", l.core),
+        ] {
+            if let Some(v) = v {
+                out.push(format!("limits.{k}={v}"));
+            }
+        }
+    }
+    if let Some(r) = runtime.filter(|r| !r.retain_caps.is_empty()) {
+        out.push(format!("retain_caps={}", r.retain_caps.join(",")));
+    }
+    if runtime.and_then(|r| r.rootfs) == Some(RootFs::Readonly) {
+        out.push("rootfs=readonly".to_string());
+    }
+    if let Some(r) = runtime {
+        for (k, on) in [
+            ("allow_realtime", r.allow_realtime),
+            ("allow_clock_set", r.allow_clock_set),
+        ] {
+            if on {
+                out.push(format!("{k}=true"));
+            }
+        }
+    }
+    out
+}
+
+fn syscalls(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .syscalls
+        .as_ref()
+        .map(|s| s.allow.clone())
+        .unwrap_or_default()
+}
+
+fn devices(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .devices
+        .as_ref()
+        .map(|d| d.paths.clone())
+        .unwrap_or_default()
+}
+
+fn ipc(m: &Manifest) -> Vec<String> {
+    let ipc = m.capabilities.ipc.unwrap_or_default();
+    let mut out = Vec::new();
+    if ipc.sysv {
+        out.push("sysv".to_string());
+    }
+    if ipc.shm {
+        out.push("/dev/shm".to_string());
+    }
+    out
+}
+
+/// Capability sections `[profile.<name>]` replaces.
+fn overridden_sections(m: &Manifest, name: &str) -> Vec<&'static str> {
+    let Some(p) = m.profile.get(name) else {
+        return Vec::new();
+    };
+    let c = &p.capabilities;
+    [
+        ("memory", c.memory.is_some()),
+        ("files", c.files.is_some()),
+        ("network", c.network.is_some()),
+        ("sockets", c.sockets.is_some()),
+        ("syscalls", c.syscalls.is_some()),
+        ("devices", c.devices.is_some()),
+        ("ipc", c.ipc.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(s, set)| set.then_some(s))
+    .collect()
+}
+
+/// Each `[profile.*]` with the capability sections it replaces.
+fn profiles(m: &Manifest) -> Vec<String> {
+    m.profile
+        .keys()
+        .map(|name| {
+            let sections = overridden_sections(m, name);
+            if sections.is_empty() {
+                format!("{name}: no overrides")
+            } else {
+                format!("{name}: overrides {}", sections.join(", "))
+            }
+        })
+        .collect()
+}
+
+fn unix_sockets(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .sockets
+        .as_ref()
+        .and_then(|s| s.unix.as_ref())
+        .map(|u| u.paths.clone())
+        .unwrap_or_default()
+}
+
+fn listen_ports(m: &Manifest) -> Vec<String> {
+    m.capabilities
+        .network
+        .as_ref()
+        .and_then(|n| n.listen.as_ref())
+        .map(|l| {
+            let ifaces = if l.interfaces.is_empty() {
+                vec!["*".to_string()]
+            } else {
+                l.interfaces.clone()
+            };
+            ifaces
+                .iter()
+                .flat_map(|i| l.ports.iter().map(move |p| format!("{i}:{p}")))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `s` as inline code; the fence is one backtick longer than any run inside `s`.
+fn code(s: &str) -> String {
+    let longest = s.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if s.starts_with('`') || s.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{pad}{s}{pad}{fence}")
+}
+
+fn list(out: &mut String, title: &str, items: &[String]) {
+    let _ = writeln!(out, "\n### {title}\n");
+    if items.is_empty() {
+        let _ = writeln!(out, "_none_");
+    }
+    for i in items {
+        let _ = writeln!(out, "- {}", code(i));
+    }
+}
+
+/// The `### ...` capability lists for `m`.
+fn capabilities_markdown(out: &mut String, m: &Manifest) {
+    let _ = writeln!(out, "\n### Memory\n");
+    match &m.capabilities.memory {
+        Some(mem) => {
+            let _ = writeln!(out, "- max_bytes: {}", mem.max_bytes);
+        }
+        None => {
+            let _ = writeln!(out, "_unbounded_");
+        }
+    }
+    list(out, "File read", &reads(m));
+    list(out, "File read denies", &denies(m));
+    let scratch: Vec<String> = tmp_max_bytes(m)
+        .map(|n| format!("/tmp (tmpfs, max_bytes {n})"))
+        .into_iter()
+        .collect();
+    list(out, "Scratch space", &scratch);
+    list(out, "Network connect", &hosts(m));
+    list(out, "Network listen", &listen_ports(m));
+    list(out, "Unix sockets", &unix_sockets(m));
+    list(out, "Syscalls allowed", &syscalls(m));
+    list(out, "Devices", &devices(m));
+    list(out, "Shared IPC", &ipc(m));
+}
+
+fn to_markdown(m: &Manifest, h: Option<&Hardening>, host: &HostSupport, gaps: &[String]) -> String {
+    let mut out = String::new();
+    let yes = |b: bool| if b { "yes" } else { "no" };
+
+    let _ = writeln!(out, "# Security report: {} {}", m.name, m.version);
+    let _ = writeln!(out, "\n## Declared capabilities");
+    capabilities_markdown(&mut out, m);
+    list(&mut out, "Secrets", &secret_names(m));
+    list(&mut out, "Runtime", &runtime_settings(m));
+    list(&mut out, "Profiles", &profiles(m));
+
+    // what the payload gets when launched with each profile
+    for name in m.profile.keys() {
+        if let Ok(merged) = m.with_profile(name) {
+            let _ = writeln!(out, "\n## Profile {}: effective capabilities", code(name));
+            capabilities_markdown(&mut out, &merged);
+        }
+    }
+
+    let _ = writeln!(out, "\n## Enforcement on this host\n");
+    let _ = writeln!(out, "| Feature | Available |\n|---|---|");
+    let _ = writeln!(out, "| Landlock | {} |", yes(host.landlock));
+    let _ = writeln!(out, "| cgroup v2 | {} |", yes(host.cgroup_v2));
+
+    if let Some(h) = h {
+        let _ = writeln!(out, "\n## Binary hardening\n");
+        let _ = writeln!(out, "| Check | Result |\n|---|---|");
+        let _ = writeln!(out, "| PIE | {} |", yes(h.pie));
+        let _ = writeln!(out, "| NX | {} |", yes(h.nx));
+        let _ = writeln!(out, "| Full RELRO | {} |", yes(h.relro && h.bind_now));
+        let _ = writeln!(out, "| Stack canary | {} |", yes(h.stack_canary));
+        let _ = writeln!(out, "| FORTIFY_SOURCE | {} |", yes(!h.fortified.is_empty()));
+        let _ = writeln!(
+            out,
+            "| Safe RPATH/RUNPATH | {} |",
+            yes(h.insecure_search_paths().is_empty())
+        );
+        let _ = writeln!(out, "\n**Score: {}/100**", h.score());
+    }
+
+    let _ = writeln!(out, "\n## Known gaps\n");
+    for g in gaps {
+        let _ = writeln!(out, "- {g}");
+    }
+    out
+}
+
+fn capabilities_json(m: &Manifest) -> Json {
+    Json::obj()
+        .with(
+            "memory_max_bytes",
+            m.capabilities.memory.as_ref().map(|x| x.max_bytes),
+        )
+        .with("read", reads(m))
+        .with("deny_read", denies(m))
+        .with("tmp_max_bytes", tmp_max_bytes(m))
+        .with("connect", hosts(m))
+        .with("listen", listen_ports(m))
+        .with("unix_sockets", unix_sockets(m))
+        .with("syscalls", syscalls(m))
+        .with("devices", devices(m))
+        .with("ipc", ipc(m))
+}
+
+fn to_json(m: &Manifest, h: Option<&Hardening>, host: &HostSupport, gaps: &[String]) -> Json {
+    let hardening = h.map(|h| {
+        Json::obj()
+            .with("pie", h.pie)
+            .with("nx", h.nx)
+            .with("full_relro", h.relro && h.bind_now)
+            .with("stack_canary", h.stack_canary)
+            .with("fortify", !h.fortified.is_empty())
+            .with("insecure_search_paths", h.insecure_search_paths())
+            .with("score", h.score())
+    });
+    // each profile with the capabilities it launches with
+    let profiles = m
+        .profile
+        .keys()
+        .fold(Json::obj(), |obj, name| match m.with_profile(name) {
+            Ok(merged) => obj.with(
+                name,
+                Json::obj()
+                    .with("overrides", overridden_sections(m, name))
+                    .with("capabilities", capabilities_json(&merged)),
+            ),
+            Err(_) => obj,
+        });
+    Json::obj()
+        .with("name", m.name.as_str())
+        .with("version", m.version.as_str())
+        .with("capabilities", capabilities_json(m))
+        .with("secrets", secret_names(m))
+        .with("runtime", runtime_settings(m))
+        .with("profiles", profiles)
+        .with(
+            "host",
+            Json::obj()
+                .with("landlock", host.landlock)
+                .with("cgroup_v2", host.cgroup_v2),
+        )
+        .with("hardening", hardening.unwrap_or(Json::Null))
+        .with("signature", "unchecked")
+        .with("gaps", gaps.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_flag_missing_limits_and_unavailable_host_features() {
+        let m = manifest::parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.network.connect]
+hosts = ["*.example.com:443"]
+"#,
+        )
+        .unwrap();
+        let host = HostSupport {
+            landlock: false,
+            cgroup_v2: true,
+        };
+        let gaps = known_gaps(&m, None, &host);
+        assert!(gaps.iter().any(|g| g.contains("memory is unbounded")));
+        assert!(gaps.iter().any(|g| g.contains("Landlock")));
+        assert!(!gaps.iter().any(|g| g.contains("cgroup v2")));
+        assert!(gaps.iter().any(|g| g.contains("Wildcard hosts")));
+
+        let md = to_markdown(&m, None, &host, &gaps);
+        assert!(md.starts_with("# Security report: demo 0.1.0"));
+        assert!(md.contains("- `*.example.com:443`"));
+    }
+
+    #[test]
+    fn syscalls_and_profile_grants_are_reported() {
+        let m = manifest::parse_manifest(
+            br#"
+name = "demo"
+version = "0.1.0"
+
+[capabilities.syscalls]
+allow = ["read", "write"]
+
+[profile.debug.capabilities.files.read]
+paths = ["/srv/`weird`"]
+"#,
+        )
+        .unwrap();
+        let host = HostSupport {
+            landlock: true,
+            cgroup_v2: true,
+        };
+        let md = to_markdown(&m, None, &host, &[]);
+        assert!(md.contains("### Syscalls allowed\n\n- `read`\n- `write`\n"));
+        assert!(md.contains("## Profile `debug`: effective capabilities"));
+        assert!(md.contains("- `` /srv/`weird` ``"));
+
+        let json = to_json(&m, None, &host, &[]).to_string();
+        assert!(json.contains("\"syscalls\": [\n      \"read\""));
+        assert!(json.contains("\"overrides\": [\n        \"files\""));
+        assert!(json.contains("\"/srv/`weird`\""));
+    }
+}
@@ -0,0 +1,468 @@
+use crate::host::HostPattern;
+use crate::manifest::{self, Manifest, RestartPolicy, RootFs};
+use anyhow::{Context, Result};
+use std::{fmt::Write, fs, net::IpAddr, path::Path};
+
+/// Write a hardened systemd service unit for `manifest_path` to `output`, or stdout.
+pub fn systemd_unit<P: AsRef<Path>>(
+    manifest_path: P,
+    exec: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let path = manifest_path.as_ref();
+    let manifest = manifest::load_manifest(path)?;
+    let default_exec = quote(&format!("/usr/local/bin/{}", manifest.name));
+    let unit = render(&manifest, exec.unwrap_or(&default_exec));
+    match output {
+        Some(o) => {
+            fs::write(o, unit).with_context(|| format!("failed to write {}", o.display()))?;
+            println!("Unit written to {}", o.display());
+        }
+        None => print!("{unit}"),
+    }
+    Ok(())
+}
+
+/// The pseudo-devices in PrivateDevices' minimal /dev.
+const PRIVATE_DEVS: &[&str] = &[
+    "/dev/null",
+    "/dev/zero",
+    "/dev/full",
+    "/dev/random",
+    "/dev/urandom",
+    "/dev/tty",
+];
+
+/// Quote one entry of a whitespace-separated setting: backslashes and `%` specifiers
+/// are escaped, and entries with whitespace or quotes are wrapped in double quotes.
+fn quote(s: &str) -> String {
+    let escaped = s.replace('\\', "\\\\").replace('%', "%%");
+    if escaped.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        format!("\"{}\"", escaped.replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+/// systemd paths have no glob support; entries with wildcards are reported instead.
+fn is_glob(p: &str) -> bool {
+    p.contains(['*', '?', '['])
+}
+
+fn render(m: &Manifest, exec: &str) -> String {
+    let caps = &m.capabilities;
+    let mut out = String::new();
+    let mut skipped = Vec::new();
+    // header lines explaining directives the manifest does not spell out itself
+    let mut notes = Vec::new();
+
+    let _ = writeln!(out, "[Unit]");
+    // Description is free text, but still subject to specifier expansion
+    let description = format!("{} {}", m.name, m.version).replace('%', "%%");
+    let _ = writeln!(out, "Description={description}");
+    let runtime = m.runtime.as_ref();
+    if let Some(n) = runtime.and_then(|r| r.max_restarts) {
+        let _ = writeln!(out, "StartLimitBurst={n}");
+    }
+
+    let _ = writeln!(out, "\n[Service]");
+    let _ = writeln!(out, "ExecStart={exec}");
+    if let Some(policy) = runtime.and_then(|r| r.restart) {
+        let restart = match policy {
+            RestartPolicy::No => "no",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Always => "always",
+        };
+        let _ = writeln!(out, "Restart={restart}");
+    }
+    if let Some(d) = runtime
+        .and_then(|r| r.restart_backoff.as_deref())
+        .and_then(manifest::parse_duration)
+    {
+        let _ = writeln!(out, "RestartSec={}ms", d.as_millis());
+    }
+
+    // baseline hardening; the manifest only ever adds access on top of this
+    for line in [
+        "DynamicUser=yes",
+        "NoNewPrivileges=yes",
+        "ProtectSystem=strict",
+        "PrivateTmp=yes",
+        "ProtectKernelTunables=yes",
+        "ProtectKernelModules=yes",
+        "ProtectControlGroups=yes",
+        "RestrictNamespaces=yes",
+        "LockPersonality=yes",
+        "MemoryDenyWriteExecute=yes",
+    ] {
+        let _ = writeln!(out, "{line}");
+    }
+    match &caps.devices {
+        None => {
+            let _ = writeln!(out, "PrivateDevices=yes");
+        }
+        Some(d) => {
+            // other nodes need the host /dev; the cgroup device policy still denies the rest
+            let minimal = d.paths.iter().all(|p| PRIVATE_DEVS.contains(&p.as_str()));
+            let _ = writeln!(out, "PrivateDevices={}", if minimal { "yes" } else { "no" });
+            let _ = writeln!(out, "DevicePolicy=closed");
+            for p in &d.paths {
+                let _ = writeln!(out, "DeviceAllow={} rw", quote(p));
+            }
+        }
+    }
+
+    let ipc = caps.ipc.unwrap_or_default();
+    if !ipc.sysv {
+        let _ = writeln!(out, "PrivateIPC=yes");
+    }
+    if !ipc.shm {
+        let _ = writeln!(out, "InaccessiblePaths=-/dev/shm");
+    }
+
+    let caps_kept: Vec<String> = runtime
+        .map(|r| r.retain_caps.iter().map(|c| format!("CAP_{c}")).collect())
+        .unwrap_or_default();
+    let _ = writeln!(out, "CapabilityBoundingSet={}", caps_kept.join(" "));
+    if !runtime.is_some_and(|r| r.allow_clock_set) {
+        let _ = writeln!(out, "ProtectClock=yes");
+    }
+    if !runtime.is_some_and(|r| r.allow_realtime) {
+        let _ = writeln!(out, "RestrictRealtime=yes");
+    }
+    if !caps_kept.is_empty() {
+        // DynamicUser runs unprivileged, so retained caps must be ambient to survive exec
+        let _ = writeln!(out, "AmbientCapabilities={}", caps_kept.join(" "));
+    }
+
+    // ProtectHome=yes would hide declared reads under home directories
+    let home_read = caps
+        .files
+        .as_ref()
+        .and_then(|f| f.read.as_ref())
+        .is_some_and(|r| {
+            r.paths.iter().any(|p| {
+                ["/home", "/root", "/run/user"]
+                    .iter()
+                    .any(|h| p == h || p.starts_with(&format!("{h}/")))
+            })
+        });
+    let protect_home = if home_read { "read-only" } else { "yes" };
+    let _ = writeln!(out, "ProtectHome={protect_home}");
+
+    if let Some(mem) = &caps.memory {
+        let _ = writeln!(out, "MemoryMax={}", mem.max_bytes);
+    }
+    if let Some(l) = runtime.and_then(|r| r.limits.as_ref()) {
+        for (key, v) in [
+            ("LimitNOFILE", l.nofile),
+            ("LimitNPROC", l.nproc),
+            ("LimitFSIZE", l.fsize),
+            ("LimitCORE", l.core),
+        ] {
+            if let Some(v) = v {
+                let _ = writeln!(out, "{key}={v}");
+            }
+        }
+    }
+
+    if runtime.and_then(|r| r.rootfs) == Some(RootFs::Readonly) {
+        // start from an empty root and bind back system dirs plus declared (non-glob) reads
+        let mut binds: Vec<String> = ["/usr", "/lib", "-/lib64", "-/bin", "-/sbin"]
+            .map(String::from)
+            .to_vec();
+        let reads = caps.files.as_ref().and_then(|f| f.read.as_ref());
+        let plain = reads
+            .into_iter()
+            .flat_map(|r| &r.paths)
+            .filter(|p| !is_glob(p));
+        binds.extend(plain.map(|p| quote(&format!("-{p}"))));
+        // the empty root has no /dev for nodes outside PrivateDevices' set
+        let devices = caps.devices.as_ref().into_iter().flat_map(|d| &d.paths);
+        binds.extend(
+            devices
+                .filter(|p| !PRIVATE_DEVS.contains(&p.as_str()))
+                .map(|p| quote(&format!("-{p}"))),
+        );
+        let _ = writeln!(out, "TemporaryFileSystem=/:ro");
+        let _ = writeln!(out, "BindReadOnlyPaths={}", binds.join(" "));
+    }
+
+    if let Some(files) = &caps.files {
+        let reads = files
+            .read
+            .as_ref()
+            .map(|r| r.paths.as_slice())
+            .unwrap_or(&[]);
+        let (globs, plain): (Vec<&String>, Vec<&String>) = reads.iter().partition(|p| is_glob(p));
+        skipped.extend(globs.into_iter().map(|p| format!("files.read {p}")));
+        if !plain.is_empty() {
+            let list: Vec<String> = plain.iter().map(|p| quote(&format!("-{p}"))).collect();
+            let _ = writeln!(out, "ReadOnlyPaths={}", list.join(" "));
+        }
+        let (globs, plain): (Vec<&String>, Vec<&String>) =
+            files.deny_read.iter().partition(|p| is_glob(p));
+        skipped.extend(globs.into_iter().map(|p| format!("files.deny_read {p}")));
+        if !plain.is_empty() {
+            let list: Vec<String> = plain.iter().map(|p| quote(&format!("-{p}"))).collect();
+            let _ = writeln!(out, "InaccessiblePaths={}", list.join(" "));
+        }
+        if let Some(tmp) = &files.tmp {
+            let _ = writeln!(out, "TemporaryFileSystem=/tmp:size={}", tmp.max_bytes);
+        }
+    }
+
+    let mut families = Vec::new();
+    let network = caps.network.as_ref();
+    let connect = network.and_then(|n| n.connect.as_ref());
+    let listen = network.and_then(|n| n.listen.as_ref());
+    if connect.is_some() || listen.is_some() {
+        families.extend(["AF_INET", "AF_INET6"]);
+    }
+    if let Some(c) = connect {
+        // IPAddressAllow filters by address only; names and ports cannot be expressed
+        let mut allow = Vec::new();
+        for h in &c.hosts {
+            match h.parse::<HostPattern>() {
+                Ok(HostPattern::Ip { addr, .. }) => allow.push(addr.to_string()),
+                Ok(HostPattern::Cidr { net, prefix, .. }) => allow.push(format!("{net}/{prefix}")),
+                _ => skipped.push(format!("network.connect {h}")),
+            }
+        }
+        // IPAddressAllow also filters inbound traffic, so a loopback listener needs it
+        let loopback_listen = listen.is_some_and(|l| l.interfaces.iter().any(|i| is_loopback(i)));
+        if loopback_listen {
+            allow.push("localhost".to_string());
+            notes.push(
+                "IPAddressAllow includes localhost for the loopback interface in network.listen.interfaces",
+            );
+        }
+        // a DNS name anywhere means addresses are unknown until resolution
+        if skipped.iter().all(|s| !s.starts_with("network.connect")) {
+            let _ = writeln!(out, "IPAddressDeny=any");
+            let _ = writeln!(out, "IPAddressAllow={}", allow.join(" "));
+        }
+    }
+    if let Some(l) = listen {
+        let _ = writeln!(out, "SocketBindDeny=any");
+        for p in &l.ports {
+            let _ = writeln!(out, "SocketBindAllow={p}");
+        }
+    }
+    if caps
+        .sockets
+        .as_ref()
+        .and_then(|s| s.unix.as_ref())
+        .is_some()
+    {
+        families.push("AF_UNIX");
+    }
+    if families.is_empty() {
+        let _ = writeln!(out, "PrivateNetwork=yes");
+        let _ = writeln!(out, "RestrictAddressFamilies=none");
+    } else {
+        let _ = writeln!(out, "RestrictAddressFamilies={}", families.join(" "));
+    }
+
+    if let Some(sys) = &caps.syscalls {
+        let _ = writeln!(out, "SystemCallFilter={}", sys.allow.join(" "));
+    }
+
+    let _ = writeln!(out, "\n[Install]");
+    let _ = writeln!(out, "WantedBy=multi-user.target");
+
+    if !skipped.is_empty() {
+        let mut note =
+            String::from("# Not expressible as systemd directives, enforce elsewhere:\n");
+        for s in &skipped {
+            let _ = writeln!(note, "#   {s}");
+        }
+        note.push('\n');
+        out.insert_str(0, &note);
+    }
+    if !notes.is_empty() {
+        let mut note = String::new();
+        for n in &notes {
+            let _ = writeln!(note, "# {n}");
+        }
+        note.push('\n');
+        out.insert_str(0, &note);
+    }
+    out
+}
+
+fn is_loopback(interface: &str) -> bool {
+    interface == "localhost"
+        || interface
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|a| a.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_capabilities_into_directives() {
+        let m = manifest::parse_manifest(
+            br#"
+name = "svc"
+version = "1.0.0"
+
+[capabilities.memory]
+max_bytes = 67108864
+
+[capabilities.files]
+deny_read = ["/etc/shadow", "/home/*/.ssh"]
+
+[capabilities.files.read]
+paths = ["/etc/svc"]
+
+[capabilities.files.tmp]
+max_bytes = 16777216
+
+[capabilities.network.connect]
+hosts = ["10.0.0.0/8:5432"]
+
+[capabilities.network.listen]
+ports = [8080]
+
+[runtime]
+restart = "on-failure"
+restart_backoff = "2s"
+retain_caps = ["NET_BIND_SERVICE"]
+allow_realtime = true
+
+[runtime.limits]
+nofile = 1024
+core = 0
+"#,
+        )
+        .unwrap();
+        let unit = render(&m, "/opt/svc/bin/svc");
+        for line in [
+            "ExecStart=/opt/svc/bin/svc",
+            "Restart=on-failure",
+            "RestartSec=2000ms",
+            "MemoryMax=67108864",
+            "ReadOnlyPaths=-/etc/svc",
+            "InaccessiblePaths=-/etc/shadow",
+            "TemporaryFileSystem=/tmp:size=16777216",
+            "IPAddressDeny=any",
+            "IPAddressAllow=10.0.0.0/8",
+            "SocketBindAllow=8080",
+            "RestrictAddressFamilies=AF_INET AF_INET6",
+            "LimitNOFILE=1024",
+            "LimitCORE=0",
+            "CapabilityBoundingSet=CAP_NET_BIND_SERVICE",
+            "AmbientCapabilities=CAP_NET_BIND_SERVICE",
+            "ProtectClock=yes",
+        ] {
+            assert!(unit.lines().any(|l| l == line), "missing {line}\n{unit}");
+        }
+        assert!(!unit.contains("RestrictRealtime"));
+        assert!(unit.starts_with("# Not expressible"));
+        assert!(unit.contains("#   files.deny_read /home/*/.ssh"));
+    }
+
+    #[test]
+    fn readonly_rootfs_and_devices_bind_only_what_is_declared() {
+        let m = manifest::parse_manifest(
+            br#"
+name = "a"
+version = "0.1.0"
+
+[capabilities.files.read]
+paths = ["/etc/a", "/srv/*/data"]
+
+[capabilities.devices]
+paths = ["/dev/urandom", "/dev/dri/renderD128"]
+
+[capabilities.ipc]
+shm = true
+
+[runtime]
+rootfs = "readonly"
+"#,
+        )
+        .unwrap();
+        let unit = render(&m, "/usr/bin/a");
+        assert!(unit.lines().any(|l| l == "TemporaryFileSystem=/:ro"));
+        assert!(unit.lines().any(|l| l
+            == "BindReadOnlyPaths=/usr /lib -/lib64 -/bin -/sbin -/etc/a -/dev/dri/renderD128"));
+        for line in [
+            "PrivateDevices=no",
+            "DevicePolicy=closed",
+            "DeviceAllow=/dev/urandom rw",
+            "DeviceAllow=/dev/dri/renderD128 rw",
+            "PrivateIPC=yes",
+        ] {
+            assert!(unit.lines().any(|l| l == line), "missing {line}\n{unit}");
+        }
+    }
+
+    #[test]
+    fn list_entries_are_quoted_and_specifiers_escaped() {
+        let m = manifest::parse_manifest(
+            br#"
+name = "100%"
+version = "0.1.0"
+
+[capabilities.files.read]
+paths = ["/srv/my app", "/srv/50%", "/srv/a\\b"]
+"#,
+        )
+        .unwrap();
+        let unit = render(&m, "/bin/a");
+        assert!(unit.contains("Description=100%% 0.1.0\n"));
+        assert!(
+            unit.contains(r#"ReadOnlyPaths="-/srv/my app" -/srv/50%% -/srv/a\\b"#),
+            "{unit}"
+        );
+    }
+
+    #[test]
+    fn no_network_means_private_network() {
+        let m = manifest::parse_manifest(b"name = \"a\"\nversion = \"0.1.0\"\n").unwrap();
+        let unit = render(&m, "/bin/a");
+        assert!(unit.contains("PrivateNetwork=yes"));
+        assert!(unit.contains("ProtectHome=yes"));
+        assert!(unit.lines().any(|l| l == "CapabilityBoundingSet="));
+        assert!(!unit.contains("TemporaryFileSystem=/:ro"));
+        assert!(unit.contains("PrivateIPC=yes"));
+        assert!(unit.contains("InaccessiblePaths=-/dev/shm"));
+        assert!(unit.contains("ProtectClock=yes") && unit.contains("RestrictRealtime=yes"));
+        assert!(!unit.contains("IPAddressAllow"));
+    }
+
+    #[test]
+    fn localhost_is_only_allowed_for_loopback_listeners() {
+        let base = "name = \"svc\"\nversion = \"0.1.0\"\n\
+                    [capabilities.network.connect]\nhosts = [\"203.0.113.7:443\"]\n";
+        let unit = render(
+            &manifest::parse_manifest(base.as_bytes()).unwrap(),
+            "/bin/svc",
+        );
+        let allow: Vec<_> = unit
+            .lines()
+            .filter(|l| l.starts_with("IPAddressAllow="))
+            .collect();
+        assert_eq!(allow, ["IPAddressAllow=203.0.113.7"]);
+        assert!(!unit.contains("localhost"));
+
+        let listening = format!(
+            "{base}[capabilities.network.listen]\nports = [8080]\ninterfaces = [\"127.0.0.1\"]\n"
+        );
+        let unit = render(
+            &manifest::parse_manifest(listening.as_bytes()).unwrap(),
+            "/bin/svc",
+        );
+        assert!(
+            unit.lines()
+                .any(|l| l == "IPAddressAllow=203.0.113.7 localhost")
+        );
+        assert!(unit.starts_with("# IPAddressAllow includes localhost"));
+    }
+}